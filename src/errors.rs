@@ -0,0 +1,84 @@
+use std::fmt;
+
+/// Errors raised when verifying a citation against the bibliography.
+#[derive(Debug, PartialEq)]
+pub enum CitationError {
+    /// The citation is malformed, e.g. it is missing a year.
+    MalformedCitation(String),
+    /// No bibliography entry matches the citation,
+    /// with the closest citation of an entry as a suggestion if there is one.
+    UnmatchedCitation(String, Option<String>),
+    /// More than one bibliography entry matches the citation, listing the candidate keys.
+    AmbiguousMatch(String, Vec<String>),
+    /// The locator of the citation is not a page, a page range or a recognized locator,
+    /// e.g. "abc" in (Hegel 2010, abc). Only checked with `strict_locators`.
+    InvalidLocator(String, String),
+    /// The author of the citation matches a single bibliography entry, but the year does not,
+    /// e.g. a transcription mistake such as (Hegel 2010) for an entry dated 2011.
+    YearMismatch {
+        citation: String,
+        year: String,
+        key: String,
+        entry_year: String,
+    },
+}
+
+impl fmt::Display for CitationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CitationError::MalformedCitation(citation) => {
+                write!(f, "Citation is malformed or is missing year: ({})", citation)
+            }
+            CitationError::UnmatchedCitation(citation, suggestion) => {
+                write!(f, "Citation not found in the library: ({})", citation)?;
+                match suggestion {
+                    Some(suggestion) => write!(f, ", did you mean ({})?", suggestion),
+                    None => Ok(()),
+                }
+            }
+            CitationError::AmbiguousMatch(citation, keys) => write!(
+                f,
+                "Citation matches multiple entries in the library: ({}) matches {:?}. \
+                 Please disambiguate the entries so that each is cited by a unique author and year",
+                citation, keys
+            ),
+            CitationError::InvalidLocator(citation, locator) => {
+                write!(f, "Citation has an invalid locator \"{}\": ({})", locator, citation)
+            }
+            CitationError::YearMismatch {
+                citation,
+                year,
+                key,
+                entry_year,
+            } => write!(
+                f,
+                "Citation year {} does not match entry {} ({}): ({})",
+                year, key, entry_year, citation
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CitationError {}
+
+/// Citation errors collected across all verified files,
+/// each paired with the path of its file and the line of the citation in the file.
+#[derive(Debug, PartialEq)]
+pub struct CitationErrors(pub Vec<(String, usize, CitationError)>);
+
+impl fmt::Display for CitationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Citation errors found:")?;
+        let mut current_path: Option<&String> = None;
+        for (path, line, error) in &self.0 {
+            if current_path != Some(path) {
+                write!(f, "\n{}", path)?;
+                current_path = Some(path);
+            }
+            write!(f, "\n  - line {}: {}", line, error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CitationErrors {}
@@ -1,49 +1,185 @@
-use biblatex::Entry;
+use biblatex::{Bibliography, Entry};
 use regex::Regex;
-use std::fs;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, create_dir_all};
 use std::io::{self, Write};
+use std::path::Path;
+use std::sync::OnceLock;
 use validators::{ArticleFileData, Metadata};
 
+use crate::utils::{OutputFormat, Settings};
+use crate::transformers::SkippedEntry;
 use crate::{transformers, validators};
 
-struct InserterOutcome {
-    total_articles_processed: i32,
-    total_bibliographies_inserted: i32,
-    total_authors_inserted: i32,
-    total_notes_headings_inserted: i32,
-    total_empty_payloads: i32,
+/// Totals of what `process` inserted into the articles.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InserterOutcome {
+    /// Number of articles passed to `process`.
+    pub total_articles: usize,
+    /// Number of articles written with their inserted content.
+    pub total_articles_processed: usize,
+    pub total_bibliographies_inserted: usize,
+    pub total_authors_inserted: usize,
+    pub total_notes_headings_inserted: usize,
+    /// Number of articles left untouched as there was nothing to insert.
+    pub total_empty_payloads: usize,
 }
 
-pub fn process_mdx_files(all_articles: Vec<ArticleFileData>) {
-    let all_articles_length = all_articles.len();
+impl std::fmt::Display for InserterOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Total articles processed: {}/{}. Inserted {} bibliographies, {} authors, and {} notes headings. {} were empty payloads",
+            self.total_articles_processed,
+            self.total_articles,
+            self.total_bibliographies_inserted,
+            self.total_authors_inserted,
+            self.total_notes_headings_inserted,
+            self.total_empty_payloads
+        )
+    }
+}
+
+/// Changes written to a single file during `process`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChange {
+    pub path: String,
+    pub bytes_added: usize,
+    pub bytes_removed: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+/// Report of the changes written by `process`, one entry per modified file.
+#[derive(Debug, Default)]
+pub struct ProcessReport {
+    /// Totals of the inserted content.
+    pub outcome: InserterOutcome,
+    pub files: Vec<FileChange>,
+    /// Entries left out of the bibliographies, paired with the path of the article citing them.
+    pub skipped_entries: Vec<(String, SkippedEntry)>,
+}
+
+impl ProcessReport {
+    pub fn total_bytes_added(&self) -> usize {
+        self.files.iter().map(|file| file.bytes_added).sum()
+    }
+
+    pub fn total_bytes_removed(&self) -> usize {
+        self.files.iter().map(|file| file.bytes_removed).sum()
+    }
+
+    pub fn total_lines_added(&self) -> usize {
+        self.files.iter().map(|file| file.lines_added).sum()
+    }
+
+    pub fn total_lines_removed(&self) -> usize {
+        self.files.iter().map(|file| file.lines_removed).sum()
+    }
+}
+
+impl std::fmt::Display for ProcessReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "Summary of changes:")?;
+        for file in &self.files {
+            writeln!(
+                f,
+                "  {}: +{} -{} lines, +{} -{} bytes",
+                file.path, file.lines_added, file.lines_removed, file.bytes_added, file.bytes_removed
+            )?;
+        }
+        write!(
+            f,
+            "  Total: {} files, +{} -{} lines, +{} -{} bytes",
+            self.files.len(),
+            self.total_lines_added(),
+            self.total_lines_removed(),
+            self.total_bytes_added(),
+            self.total_bytes_removed()
+        )
+    }
+}
+
+pub fn process_mdx_files(all_articles: Vec<ArticleFileData>, settings: &Settings) -> ProcessReport {
     let mut inserter_outcome = InserterOutcome {
-        total_articles_processed: 0,
-        total_bibliographies_inserted: 0,
-        total_authors_inserted: 0,
-        total_notes_headings_inserted: 0,
-        total_empty_payloads: 0,
+        total_articles: all_articles.len(),
+        ..Default::default()
     };
+    let mut process_report = ProcessReport::default();
+    // Citations are linked through a single index of the entries cited across all articles
+    let cited_entries = collect_cited_entries(&all_articles);
+    let citation_index = validators::build_citation_index(&cited_entries);
 
     for article in all_articles {
-        process_mdx_file(article, &mut inserter_outcome);
+        let path = article.path.clone();
+        let mut skipped_entries = Vec::new();
+        if let Some(file_change) = process_mdx_file(
+            article,
+            &citation_index,
+            settings,
+            &mut inserter_outcome,
+            &mut skipped_entries,
+        ) {
+            process_report.files.push(file_change);
+        }
+        for skipped_entry in skipped_entries {
+            process_report.skipped_entries.push((path.clone(), skipped_entry));
+        }
     }
-    println!(
-        "✓ Processing OK. Total articles processed: {}/{}. Inserted {} bibliographies, {} authors, and {} notes headings. {} were empty payloads",
-        inserter_outcome.total_articles_processed,
-        all_articles_length,
-        inserter_outcome.total_bibliographies_inserted,
-        inserter_outcome.total_authors_inserted,
-        inserter_outcome.total_notes_headings_inserted,
-        inserter_outcome.total_empty_payloads
-    );
+    report_skipped_entries(&process_report.skipped_entries);
+    if settings.dry_run {
+        println!("✓ Dry run OK, no files were written. {}", inserter_outcome);
+    } else {
+        println!("✓ Processing OK. {}", inserter_outcome);
+    }
+    process_report.outcome = inserter_outcome;
+    if settings.summary || settings.dry_run {
+        println!("{}", process_report);
+    }
+    process_report
 }
 
-fn process_mdx_file(article_file_data: ArticleFileData, inserter_outcome: &mut InserterOutcome) {
+fn process_mdx_file(
+    article_file_data: ArticleFileData,
+    citation_index: &HashMap<String, Vec<&Entry>>,
+    settings: &Settings,
+    inserter_outcome: &mut InserterOutcome,
+    skipped_entries: &mut Vec<SkippedEntry>,
+) -> Option<FileChange> {
+    if settings.emit_metadata_json && !settings.dry_run {
+        if let Err(err) = write_metadata_json(&article_file_data) {
+            eprintln!("Error writing metadata JSON: {}", err);
+            std::process::exit(1);
+        }
+    }
+
     let mut mdx_payload = String::new();
-    let mdx_bibliography = generate_mdx_bibliography(article_file_data.matched_citations);
+    // Only the entries listed in the bibliography have an anchor to link to
+    let mut linked_entries: Vec<Entry> = article_file_data
+        .matched_citations
+        .iter()
+        .filter(|entry| transformers::is_supported_entry_type(&entry.entry_type))
+        .cloned()
+        .collect();
+    // Articles citing only a few works can go without a bibliography section
+    let mdx_bibliography =
+        if meets_bibliography_threshold(article_file_data.matched_citations.len(), settings) {
+            generate_mdx_bibliography(article_file_data.matched_citations, settings, skipped_entries)
+        } else {
+            String::new()
+        };
 
-    let mdx_authors = generate_mdx_authors(&article_file_data.metadata);
-    let mdx_notes_heading = generate_notes_heading(&article_file_data.markdown_content);
+    // A bibliography-only run leaves out everything but the bibliography,
+    // keeping the sections of an earlier run in place
+    let (mdx_authors, mdx_notes_heading) = if settings.bibliography_only {
+        (String::new(), String::new())
+    } else {
+        (
+            generate_mdx_authors(&article_file_data.metadata),
+            generate_notes_heading(&article_file_data.markdown_content),
+        )
+    };
 
     if !mdx_bibliography.is_empty() {
         mdx_payload.push_str(&mdx_bibliography);
@@ -59,11 +195,50 @@ fn process_mdx_file(article_file_data: ArticleFileData, inserter_outcome: &mut I
     }
     if mdx_payload.is_empty() {
         inserter_outcome.total_empty_payloads += 1;
-        return;
     }
 
-    let updated_markdown_content =
-        format!("{}\n{}", article_file_data.full_file_content, mdx_payload);
+    // Citations are only linked when there is a bibliography to link to
+    if mdx_bibliography.is_empty() {
+        linked_entries.clear();
+    }
+    let updated_markdown_content = append_payload(
+        &article_file_data.full_file_content,
+        &mdx_payload,
+        citation_index,
+        &linked_entries,
+        settings,
+    );
+    // An empty payload still removes the sections of an earlier run
+    if mdx_payload.is_empty() && updated_markdown_content == article_file_data.full_file_content {
+        return None;
+    }
+
+    // A dry run previews the changes instead of writing them
+    if settings.dry_run {
+        inserter_outcome.total_articles_processed += 1;
+        println!("---Dry run: {} would change", article_file_data.path);
+        print!(
+            "{}",
+            unified_diff(
+                &article_file_data.path,
+                &article_file_data.full_file_content,
+                &updated_markdown_content,
+            )
+        );
+        return Some(compare_contents(
+            &article_file_data.path,
+            &article_file_data.full_file_content,
+            &updated_markdown_content,
+        ));
+    }
+
+    // Only files that actually change are backed up
+    if updated_markdown_content != article_file_data.full_file_content {
+        if let Err(err) = backup_file(&article_file_data.path, settings) {
+            eprintln!("Error backing up MDX file: {}", err);
+            std::process::exit(1);
+        }
+    }
 
     match write_html_to_mdx_file(&article_file_data.path, &updated_markdown_content) {
         Ok(_) => {
@@ -72,6 +247,11 @@ fn process_mdx_file(article_file_data: ArticleFileData, inserter_outcome: &mut I
                 "---Success! HTML bibliography inserted for {}",
                 article_file_data.path
             );
+            Some(compare_contents(
+                &article_file_data.path,
+                &article_file_data.full_file_content,
+                &updated_markdown_content,
+            ))
         }
         Err(err) => {
             eprintln!("Error writing HTML to MDX file: {}", err);
@@ -80,39 +260,553 @@ fn process_mdx_file(article_file_data: ArticleFileData, inserter_outcome: &mut I
     }
 }
 
+/// Append the payload to the content of an article, replacing the sections injected
+/// by an earlier run. A bibliography-only run replaces the bibliography alone and keeps
+/// the authors, editors, contributors and notes sections of the earlier run.
+/// The frontmatter is left untouched, custom keys included, and only the body
+/// is rewritten when citations are linked to the entries of the bibliography.
+fn append_payload(
+    full_file_content: &str,
+    mdx_payload: &str,
+    citation_index: &HashMap<String, Vec<&Entry>>,
+    linked_entries: &Vec<Entry>,
+    settings: &Settings,
+) -> String {
+    let line_ending = detect_line_ending(full_file_content);
+    let (mut original_content, injected_sections) =
+        split_injected_content(full_file_content, settings);
+    // Sections the run does not emit again are kept after the new payload
+    let kept_sections: String = if settings.bibliography_only {
+        let bibliography_heading = generate_bibliography_heading(settings);
+        injected_sections
+            .into_iter()
+            .filter(|(heading, _)| *heading != bibliography_heading)
+            .map(|(_, section)| section)
+            .collect()
+    } else {
+        String::new()
+    };
+    if settings.link_citations && !linked_entries.is_empty() {
+        original_content =
+            validators::link_citations_in_mdx(&original_content, citation_index, linked_entries, settings);
+    }
+    if mdx_payload.is_empty() && kept_sections.is_empty() {
+        return original_content;
+    }
+    let mut updated_markdown_content = format!(
+        "{}{}{}",
+        original_content,
+        apply_line_ending(&format!("\n{}", mdx_payload), line_ending),
+        kept_sections
+    );
+    if !settings.keep_trailing_newlines {
+        updated_markdown_content =
+            end_with_single_line_ending(&updated_markdown_content, line_ending);
+    }
+    updated_markdown_content
+}
+
+/// Sidecar written next to an article with its parsed frontmatter.
+#[derive(Serialize)]
+struct MetadataSidecar<'a> {
+    path: &'a str,
+    metadata: &'a Metadata,
+}
+
+/// Path of the metadata sidecar of an article, i.e. `<path>.meta.json`.
+fn metadata_json_path(mdx_path: &str) -> String {
+    format!("{}.meta.json", mdx_path)
+}
+
+/// Write the parsed frontmatter of the article to `<path>.meta.json`,
+/// so that downstream build tools do not have to parse the frontmatter again.
+fn write_metadata_json(article_file_data: &ArticleFileData) -> io::Result<()> {
+    let sidecar = MetadataSidecar {
+        path: &article_file_data.path,
+        metadata: &article_file_data.metadata,
+    };
+    let json = serde_json::to_string_pretty(&sidecar)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+    write_html_to_mdx_file(
+        &metadata_json_path(&article_file_data.path),
+        &end_with_single_line_ending(&json, "\n"),
+    )
+}
+
+/// Print the entries left out of the bibliographies once, grouped by entry type.
+fn report_skipped_entries(skipped_entries: &[(String, SkippedEntry)]) {
+    let mut skipped_by_type: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (path, skipped_entry) in skipped_entries {
+        skipped_by_type
+            .entry(&skipped_entry.entry_type)
+            .or_default()
+            .push(format!("{} in {}", skipped_entry.key, path));
+    }
+    for (entry_type, entries) in skipped_by_type {
+        println!(
+            "⚠ Entry type not supported: {} ({} entries left out): {}",
+            entry_type,
+            entries.len(),
+            entries.join(", ")
+        );
+    }
+}
+
+/// Count the bytes and lines that differ between the original and the updated content,
+/// ignoring the lines both have in common at the start and at the end.
+fn compare_contents(path: &str, original: &str, updated: &str) -> FileChange {
+    let original_lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let updated_lines: Vec<&str> = updated.split_inclusive('\n').collect();
+    let (common_prefix, common_suffix) = common_lines(&original_lines, &updated_lines);
+
+    let removed = &original_lines[common_prefix..original_lines.len() - common_suffix];
+    let added = &updated_lines[common_prefix..updated_lines.len() - common_suffix];
+
+    FileChange {
+        path: path.to_string(),
+        bytes_added: added.iter().map(|line| line.len()).sum(),
+        bytes_removed: removed.iter().map(|line| line.len()).sum(),
+        lines_added: added.len(),
+        lines_removed: removed.len(),
+    }
+}
+
+/// Number of lines the original and updated content have in common at their start and at their end.
+fn common_lines(original_lines: &[&str], updated_lines: &[&str]) -> (usize, usize) {
+    let common_prefix = original_lines
+        .iter()
+        .zip(updated_lines.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let common_suffix = original_lines[common_prefix..]
+        .iter()
+        .rev()
+        .zip(updated_lines[common_prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (common_prefix, common_suffix)
+}
+
+/// Number of unchanged lines shown around the changed lines of a diff.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Unified diff of the changes `process` makes to a file, as a single hunk spanning
+/// from the first to the last changed line. Empty if the content is unchanged.
+fn unified_diff(path: &str, original: &str, updated: &str) -> String {
+    let original_lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let updated_lines: Vec<&str> = updated.split_inclusive('\n').collect();
+    let (common_prefix, common_suffix) = common_lines(&original_lines, &updated_lines);
+    if common_prefix == original_lines.len() && common_prefix == updated_lines.len() {
+        return String::new();
+    }
+
+    let start = common_prefix.saturating_sub(DIFF_CONTEXT_LINES);
+    let context_after = common_suffix.min(DIFF_CONTEXT_LINES);
+    let original_end = original_lines.len() - common_suffix + context_after;
+    let updated_end = updated_lines.len() - common_suffix + context_after;
+    // The start of an empty range is the line before it
+    let hunk_range = |end: usize| {
+        let count = end - start;
+        format!("{},{}", if count == 0 { start } else { start + 1 }, count)
+    };
+
+    let mut diff = format!("--- a/{}\n+++ b/{}\n", path, path);
+    diff.push_str(&format!("@@ -{} +{} @@\n", hunk_range(original_end), hunk_range(updated_end)));
+    let diff_line =
+        |marker: char, line: &str| format!("{}{}\n", marker, line.trim_end_matches(['\r', '\n']));
+    for line in &original_lines[start..common_prefix] {
+        diff.push_str(&diff_line(' ', line));
+    }
+    for line in &original_lines[common_prefix..original_lines.len() - common_suffix] {
+        diff.push_str(&diff_line('-', line));
+    }
+    for line in &updated_lines[common_prefix..updated_lines.len() - common_suffix] {
+        diff.push_str(&diff_line('+', line));
+    }
+    for line in &original_lines[original_lines.len() - common_suffix..original_end] {
+        diff.push_str(&diff_line(' ', line));
+    }
+    diff
+}
+
+/// Headings of the sections `process` appends to an article after the bibliography,
+/// in the order they are appended.
+const INJECTED_HEADINGS: [&str; 4] = ["**Authors**", "**Editors**", "**Contributors**", "**Notes**"];
+
+/// Heading of the bibliography section, e.g. `## Bibliography`, as configured in the settings.
+fn generate_bibliography_heading(settings: &Settings) -> String {
+    format!(
+        "{} {}",
+        "#".repeat(settings.bibliography_heading_level.unwrap_or(2)),
+        settings.bibliography_heading.as_deref().unwrap_or("Bibliography")
+    )
+}
+
+/// Remove the sections previously appended by `process` from the MDX files,
+/// restoring the original frontmatter and body. Returns the number of files cleaned.
+/// The bibliography heading is recognized as configured in the settings.
+pub fn clean_mdx_files(mdx_paths: Vec<String>, settings: &Settings) -> io::Result<usize> {
+    let mut cleaned_count = 0;
+    for mdx_path in &mdx_paths {
+        let content = fs::read_to_string(mdx_path)?;
+        let cleaned_content = strip_injected_content(&content, settings);
+        if cleaned_content != content {
+            backup_file(mdx_path, settings)?;
+            write_html_to_mdx_file(mdx_path, &cleaned_content)?;
+            println!("---Success! Injected content removed from {}", mdx_path);
+            cleaned_count += 1;
+        }
+    }
+    println!(
+        "✓ Cleaning OK. Total files cleaned: {}/{}",
+        cleaned_count,
+        mdx_paths.len()
+    );
+    Ok(cleaned_count)
+}
+
+/// Strip the bibliography, authors, editors, contributors and notes sections appended by `process`
+/// from the end of the content, along with the blank line separating them from the body.
+/// Content without such sections at its end is returned as it is.
+pub fn strip_injected_content(content: &str, settings: &Settings) -> String {
+    split_injected_content(content, settings).0
+}
+
+/// Split the content into its body and the sections appended by `process`, keyed by heading
+/// and in the order they were appended, each starting with the blank line opening it.
+/// The blank line separating the sections from the body belongs to neither.
+fn split_injected_content(content: &str, settings: &Settings) -> (String, Vec<(String, String)>) {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut end = lines.len();
+    let mut sections: Vec<(String, String)> = Vec::new();
+    let bibliography_heading = generate_bibliography_heading(settings);
+
+    // The sections are stripped in the reverse order of appending, the bibliography last
+    let mut headings: Vec<&str> = INJECTED_HEADINGS.iter().rev().copied().collect();
+    headings.push(&bibliography_heading);
+
+    for heading in headings {
+        let is_bibliography = heading == bibliography_heading;
+        if let Some(start) = find_injected_section(&lines[..end], heading, is_bibliography) {
+            sections.insert(0, (heading.to_string(), lines[start..end].concat()));
+            end = start;
+        }
+    }
+    if sections.is_empty() {
+        return (content.to_string(), sections);
+    }
+    // The payload is separated from the body by a blank line
+    if end > 0 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    (lines[..end].concat(), sections)
+}
+
+/// Index of the blank line opening the section under the heading, if the section ends the lines.
+/// Only the bibliography has a blank line in its body, right after its heading,
+/// and the notes heading has no body at all.
+fn find_injected_section(lines: &[&str], heading: &str, is_bibliography: bool) -> Option<usize> {
+    let heading_index = lines.iter().rposition(|line| line.trim_end() == heading)?;
+    if heading_index == 0 || !lines[heading_index - 1].trim().is_empty() {
+        return None;
+    }
+    let body = &lines[heading_index + 1..];
+    let body = match heading {
+        _ if is_bibliography => match body.split_first() {
+            Some((first, rest)) if first.trim().is_empty() && !rest.is_empty() => rest,
+            _ => return None,
+        },
+        "**Notes**" if !body.is_empty() => return None,
+        "**Notes**" => body,
+        _ if body.is_empty() => return None,
+        _ => body,
+    };
+    if body.iter().any(|line| line.trim().is_empty()) {
+        return None;
+    }
+    Some(heading_index - 1)
+}
+
+/// Collect the entries cited across all articles, deduplicated by key, in the order they are first cited.
+fn collect_cited_entries(all_articles: &Vec<ArticleFileData>) -> Vec<Entry> {
+    let mut all_entries: Vec<Entry> = Vec::new();
+    for article in all_articles {
+        for entry in &article.matched_citations {
+            if !all_entries.iter().any(|existing| existing.key == entry.key) {
+                all_entries.push(entry.clone());
+            }
+        }
+    }
+    all_entries
+}
+
+/// Write the entries cited across all articles, deduplicated by key, to a BibTeX file
+/// at the output path, e.g. to ship a minimal bibliography alongside exported content.
+pub fn generate_used_bib_to_file(all_articles: &Vec<ArticleFileData>, out_path: &str) -> io::Result<()> {
+    let mut bibliography = Bibliography::new();
+    for entry in collect_cited_entries(all_articles) {
+        bibliography.insert(entry);
+    }
+    let entries_count = bibliography.len();
+
+    if let Some(parent) = Path::new(out_path).parent() {
+        create_dir_all(parent)?;
+    }
+    fs::write(out_path, bibliography.to_biblatex_string())?;
+    println!(
+        "✓ Bibliography of the {} cited entries written to {}",
+        entries_count, out_path
+    );
+    Ok(())
+}
+
+/// Generate a single bibliography containing the union of the works cited
+/// across all articles, deduplicated by key, and write it to the output path.
+/// When nothing is cited, a note saying so is written instead of an empty list.
+pub fn generate_master_bibliography_to_file(
+    all_articles: &Vec<ArticleFileData>,
+    out_path: &str,
+    settings: &Settings,
+) -> io::Result<()> {
+    let all_entries = collect_cited_entries(all_articles);
+    let entries_count = all_entries.len();
+    let master_bibliography = if all_entries.is_empty() {
+        format!(
+            "{}\n\nNo works are cited in the processed articles.\n",
+            generate_bibliography_heading(settings)
+        )
+    } else {
+        // Skipped entries are reported per article by `process`
+        generate_mdx_bibliography(all_entries, settings, &mut Vec::new())
+    };
+
+    if let Some(parent) = Path::new(out_path).parent() {
+        create_dir_all(parent)?;
+    }
+    write_html_to_mdx_file(out_path, master_bibliography.trim_start())?;
+    println!(
+        "✓ Master bibliography with {} entries written to {}",
+        entries_count, out_path
+    );
+    Ok(())
+}
+
+/// Generate an index of the articles grouped by their frontmatter tags and write it to the output path.
+/// Tags are listed alphabetically, each with links to its articles sorted by title.
+/// Articles are linked by their path without the `.mdx` extension.
+pub fn generate_tag_index_to_file(all_articles: &Vec<ArticleFileData>, out_path: &str) -> io::Result<()> {
+    let tag_index = generate_tag_index(all_articles);
+
+    if let Some(parent) = Path::new(out_path).parent() {
+        create_dir_all(parent)?;
+    }
+    write_html_to_mdx_file(out_path, &tag_index)?;
+    println!("✓ Tag index written to {}", out_path);
+    Ok(())
+}
+
+fn generate_tag_index(all_articles: &Vec<ArticleFileData>) -> String {
+    let mut articles_by_tag: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+    for article in all_articles {
+        for tag in &article.metadata.tags {
+            let tag = tag.trim();
+            if tag.is_empty() {
+                continue;
+            }
+            let link = match Path::new(&article.path).extension() {
+                Some(extension) => &article.path[..article.path.len() - extension.len() - 1],
+                None => &article.path,
+            };
+            let articles = articles_by_tag.entry(tag).or_default();
+            if !articles.iter().any(|(_, existing)| *existing == link) {
+                articles.push((&article.metadata.title, link));
+            }
+        }
+    }
+
+    let mut tag_index = String::from("## Tags\n");
+    if articles_by_tag.is_empty() {
+        tag_index.push_str("\nNo tags are used in the processed articles.\n");
+        return tag_index;
+    }
+    for (tag, mut articles) in articles_by_tag {
+        articles.sort();
+        tag_index.push_str(&format!("\n### {}\n\n", tag));
+        for (title, link) in articles {
+            tag_index.push_str(&format!("- [{}]({})\n", title, link));
+        }
+    }
+    tag_index
+}
+
+/// Suffix of the backups made with the `backup` setting, unless `backup_suffix` is set.
+pub const DEFAULT_BACKUP_SUFFIX: &str = ".bak";
+
+/// Copy the file to `<path><suffix>` before it is overwritten, if backups are enabled in the settings.
+fn backup_file(path: &str, settings: &Settings) -> io::Result<()> {
+    if !settings.backup {
+        return Ok(());
+    }
+    let suffix = settings
+        .backup_suffix
+        .as_deref()
+        .filter(|suffix| !suffix.is_empty())
+        .unwrap_or(DEFAULT_BACKUP_SUFFIX);
+    fs::copy(path, format!("{}{}", path, suffix))?;
+    Ok(())
+}
+
+/// Write the content to the file, keeping the permissions of an existing file.
+/// The content is written to a sibling temporary file which is then renamed over the file,
+/// so that the file is never left half-written.
 fn write_html_to_mdx_file(path: &str, content: &str) -> io::Result<()> {
-    let file = fs::File::create(path)?;
+    let original_permissions = fs::metadata(path).map(|metadata| metadata.permissions()).ok();
+    let temp_path = temp_file_path(path);
+
+    let written = write_temp_file(&temp_path, content).and_then(|_| {
+        if let Some(permissions) = original_permissions {
+            fs::set_permissions(&temp_path, permissions)?;
+        }
+        fs::rename(&temp_path, path)
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written
+}
+
+/// Path of the temporary file the content of a file is written to before replacing it,
+/// i.e. `<path>.tmp`, in the same directory so that it can be renamed over the file.
+fn temp_file_path(path: &str) -> String {
+    format!("{}.tmp", path)
+}
+
+/// Write the content to the temporary file and flush it to disk.
+fn write_temp_file(temp_path: &str, content: &str) -> io::Result<()> {
+    let file = fs::File::create(temp_path)?;
     let mut writer = io::BufWriter::new(file);
     writer.write_all(content.as_bytes())?;
-    Ok(())
+    writer.flush()?;
+    writer.get_ref().sync_all()
+}
+
+/// Detect the dominant line ending of the content, defaulting to `\n`.
+fn detect_line_ending(content: &str) -> &'static str {
+    let crlf_count = content.matches("\r\n").count();
+    let lf_count = content.matches('\n').count() - crlf_count;
+    if crlf_count > lf_count {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// End the content with exactly one line ending, avoiding both a missing newline
+/// at the end of the file and trailing blank lines.
+fn end_with_single_line_ending(content: &str, line_ending: &str) -> String {
+    format!("{}{}", content.trim_end_matches(['\r', '\n']), line_ending)
+}
+
+/// Convert the line endings of generated text to the given line ending.
+fn apply_line_ending(text: &str, line_ending: &str) -> String {
+    if line_ending == "\n" {
+        return text.to_string();
+    }
+    text.replace("\r\n", "\n").replace('\n', line_ending)
 }
 
-fn generate_mdx_bibliography(entries: Vec<Entry>) -> String {
+/// Whether enough distinct works are cited for a bibliography section,
+/// as set by `min_citations_for_bibliography`. Always true when unset.
+fn meets_bibliography_threshold(cited_works_count: usize, settings: &Settings) -> bool {
+    match settings.min_citations_for_bibliography {
+        Some(min_citations) => cited_works_count >= min_citations,
+        None => true,
+    }
+}
+
+/// Generate the bibliography section. The heading, the markup wrapping the list and each entry
+/// can be customized through the settings, e.g. to use custom JSX components.
+fn generate_mdx_bibliography(
+    entries: Vec<Entry>,
+    settings: &Settings,
+    skipped_entries: &mut Vec<SkippedEntry>,
+) -> String {
     let mut bib_html = String::new();
 
     if entries.is_empty() {
         return bib_html;
     }
 
-    let prepared_entries = transformers::entries_to_strings(entries);
-
-    bib_html.push_str("\n## Bibliography\n\n<div className=\"text-sm\">\n");
+    let no_date_token = settings
+        .no_date_token
+        .as_deref()
+        .unwrap_or(transformers::DEFAULT_NO_DATE_TOKEN);
+    let prepared_entries =
+        transformers::entries_to_keyed_strings(entries, skipped_entries, no_date_token);
+    if prepared_entries.is_empty() {
+        // None of the cited works have a supported entry type
+        return match &settings.unsupported_bibliography_placeholder {
+            Some(placeholder) => {
+                format!("\n{}\n\n{}\n", generate_bibliography_heading(settings), placeholder)
+            }
+            None => bib_html,
+        };
+    }
+    // Plain Markdown has no wrapper around the list unless one is configured
+    let (default_open, default_close) = match settings.output_format {
+        OutputFormat::Jsx => (Some("<div className=\"text-sm\">"), Some("</div>")),
+        OutputFormat::Markdown => (None, None),
+    };
+    let bibliography_open = settings.bibliography_open.as_deref().or(default_open);
+    let bibliography_close = settings.bibliography_close.as_deref().or(default_close);
+    let entry_template = settings
+        .bibliography_entry_template
+        .as_deref()
+        .unwrap_or("- {entry}");
 
-    for entry in prepared_entries {
-        bib_html.push_str("- ");
-        bib_html.push_str(&entry);
+    bib_html.push_str("\n");
+    bib_html.push_str(&generate_bibliography_heading(settings));
+    bib_html.push_str("\n\n");
+    if let Some(bibliography_open) = bibliography_open {
+        bib_html.push_str(bibliography_open);
         bib_html.push_str("\n");
     }
 
-    bib_html.push_str("</div>\n");
+    for (key, entry) in prepared_entries {
+        let mut entry = escape_jsx(&entry);
+        if settings.link_citations {
+            entry = format!("<span id=\"{}\"></span>{}", citation_anchor_id(&key), entry);
+        }
+        bib_html.push_str(&entry_template.replace("{entry}", &entry));
+        bib_html.push_str("\n");
+    }
 
-    bib_html = bib_html.replace("..", ".");
-    bib_html = bib_html.replace("...", ".");
-    bib_html = bib_html.replace("....", ".");
+    if let Some(bibliography_close) = bibliography_close {
+        bib_html.push_str(bibliography_close);
+        bib_html.push_str("\n");
+    }
 
     bib_html
 }
 
+/// Id of the bibliography anchor of an entry, e.g. "cite-hegel2010logic" for the key "hegel2010logic".
+/// Characters not allowed in a URL fragment are replaced with hyphens.
+pub fn citation_anchor_id(key: &str) -> String {
+    let key: String = key
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    format!("cite-{}", key)
+}
+
+/// Escape characters that MDX would otherwise read as JSX or expressions.
+fn escape_jsx(text: &str) -> String {
+    text.replace('{', "\\{")
+        .replace('}', "\\}")
+        .replace('<', "&lt;")
+}
+
 fn generate_mdx_authors(metadata: &Metadata) -> String {
     let mut mdx_html = String::new();
 
@@ -138,13 +832,539 @@ fn generate_mdx_authors(metadata: &Metadata) -> String {
 fn generate_notes_heading(markdown: &String) -> String {
     let mut mdx_notes_heading = String::new();
 
-    let footnote_regex = Regex::new(r"\[\^1\]").unwrap();
+    // Matches both footnote references, e.g. "[^1]" or "[^intro]", and definitions, e.g. "[^1]: A note."
+    static FOOTNOTE_REGEX: OnceLock<Regex> = OnceLock::new();
+    let footnote_regex = FOOTNOTE_REGEX.get_or_init(|| Regex::new(r"\[\^[^\]]+\]").unwrap());
 
-    'outer: for line in markdown.lines() {
-        for _captures in footnote_regex.captures_iter(line) {
-            mdx_notes_heading.push_str("\n**Notes**");
-            break 'outer;
-        }
+    if markdown.lines().any(|line| footnote_regex.is_match(line)) {
+        mdx_notes_heading.push_str("\n**Notes**");
     }
     mdx_notes_heading
 }
+
+#[cfg(test)]
+mod tests_notes_heading {
+    use super::*;
+
+    #[test]
+    fn numeric_footnote() {
+        let markdown = "Some text.[^1]\n\n[^1]: A note.\n".to_string();
+        assert_eq!(generate_notes_heading(&markdown), "\n**Notes**");
+    }
+    #[test]
+    fn numeric_footnote_not_starting_at_one() {
+        let markdown = "Some text.[^2]\n".to_string();
+        assert_eq!(generate_notes_heading(&markdown), "\n**Notes**");
+    }
+    #[test]
+    fn named_footnotes() {
+        let markdown = "Some text.[^intro] More text.[^note-a]\n".to_string();
+        assert_eq!(generate_notes_heading(&markdown), "\n**Notes**");
+    }
+    #[test]
+    fn footnote_definition_only() {
+        let markdown = "Some text.\n\n[^intro]: A note.\n".to_string();
+        assert_eq!(generate_notes_heading(&markdown), "\n**Notes**");
+    }
+    #[test]
+    fn no_footnotes() {
+        let markdown = "Some text [with brackets] and a caret ^1.\n\n[^]: Not a note.\n".to_string();
+        assert_eq!(generate_notes_heading(&markdown), "");
+    }
+}
+
+#[cfg(test)]
+mod tests_line_endings {
+    use super::*;
+
+    #[test]
+    fn detect_lf() {
+        assert_eq!(detect_line_ending("a\nb\nc\n"), "\n");
+        assert_eq!(detect_line_ending("no line ending"), "\n");
+    }
+    #[test]
+    fn detect_crlf() {
+        assert_eq!(detect_line_ending("a\r\nb\r\nc\n"), "\r\n");
+    }
+    #[test]
+    fn single_trailing_newline() {
+        assert_eq!(end_with_single_line_ending("a\n**Notes**", "\n"), "a\n**Notes**\n");
+        assert_eq!(end_with_single_line_ending("a\n\n\n", "\n"), "a\n");
+        assert_eq!(end_with_single_line_ending("a\r\n\r\n", "\r\n"), "a\r\n");
+    }
+    #[test]
+    fn apply_crlf() {
+        assert_eq!(apply_line_ending("\na\nb\r\n", "\r\n"), "\r\na\r\nb\r\n");
+        assert_eq!(apply_line_ending("\na\nb\n", "\n"), "\na\nb\n");
+    }
+}
+
+#[cfg(test)]
+mod tests_atomic_write {
+    use super::*;
+    use crate::test_utils::ArticleFixture;
+
+    #[test]
+    fn write_replaces_file_without_temp_file() {
+        let fixture = ArticleFixture::new(
+            "atomic_write_test",
+            "# Title\nOriginal text that is longer than the new text.\n",
+        );
+
+        let written = write_html_to_mdx_file(&fixture.path, "# Title\nNew text.\n");
+
+        assert!(written.is_ok());
+        assert_eq!(fixture.read(), "# Title\nNew text.\n");
+        assert_eq!(fixture.file_names(), vec!["article.mdx"]);
+    }
+    #[test]
+    fn failed_rename_leaves_no_temp_file() {
+        // A directory cannot be replaced by the written file
+        let dir_path = "tests/mocks/atomic_write_dir_target";
+        create_dir_all(dir_path).expect("Failed to create directory");
+
+        let written = write_html_to_mdx_file(dir_path, "# Title\n");
+        let temp_file_exists = Path::new(&temp_file_path(dir_path)).exists();
+        fs::remove_dir_all(dir_path).expect("Failed to remove directory");
+
+        assert!(written.is_err());
+        assert!(!temp_file_exists);
+    }
+}
+
+#[cfg(test)]
+mod tests_dry_run {
+    use super::*;
+    use crate::test_utils::ArticleFixture;
+
+    #[test]
+    fn dry_run_leaves_files_unchanged() {
+        let content = "---\ntitle: Test\n---\n\nSome text.[^1]\n\n[^1]: A note.\n";
+        let fixture = ArticleFixture::new("dry_run_test", content);
+        let settings = Settings {
+            dry_run: true,
+            emit_metadata_json: true,
+            ..Default::default()
+        };
+
+        let report = process_mdx_files(vec![fixture.article()], &settings);
+
+        assert_eq!(fixture.read(), content);
+        assert_eq!(fixture.file_names(), vec!["article.mdx"]);
+        assert_eq!(report.outcome.total_authors_inserted, 1);
+        assert_eq!(report.outcome.total_notes_headings_inserted, 1);
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.files[0].lines_removed, 0);
+    }
+    #[test]
+    fn diff_of_appended_content() {
+        assert_eq!(
+            unified_diff("a.mdx", "a\nb\nc\nd\n", "a\nb\nc\nd\n\n**Notes**\n"),
+            "--- a/a.mdx\n+++ b/a.mdx\n@@ -2,3 +2,5 @@\n b\n c\n d\n+\n+**Notes**\n"
+        );
+    }
+    #[test]
+    fn diff_of_replaced_line() {
+        assert_eq!(
+            unified_diff("a.mdx", "1\n2\n3\n4\n5\n6\n7\n8\n9\n", "1\n2\n3\n4\nfive\n6\n7\n8\n9\n"),
+            "--- a/a.mdx\n+++ b/a.mdx\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+        );
+    }
+    #[test]
+    fn diff_of_crlf_content() {
+        assert_eq!(
+            unified_diff("a.mdx", "a\r\n", "a\r\n\r\n**Notes**\r\n"),
+            "--- a/a.mdx\n+++ b/a.mdx\n@@ -1,1 +1,3 @@\n a\n+\n+**Notes**\n"
+        );
+    }
+    #[test]
+    fn no_diff_of_unchanged_content() {
+        assert_eq!(unified_diff("a.mdx", "a\nb\n", "a\nb\n"), "");
+    }
+}
+
+#[cfg(test)]
+mod tests_backup {
+    use super::*;
+    use crate::test_utils::ArticleFixture;
+
+    #[test]
+    fn backup_matches_original_content() {
+        let content = "---\ntitle: Test\n---\n\nSome text.\n";
+        let fixture = ArticleFixture::new("backup_test", content);
+        let backup_path = format!("{}.bak", fixture.path);
+        let settings = Settings {
+            backup: true,
+            ..Default::default()
+        };
+
+        process_mdx_files(vec![fixture.article()], &settings);
+        let backup_content = fs::read_to_string(&backup_path);
+        let processed_content = fixture.read();
+        fs::remove_file(&backup_path).expect("Failed to remove backup");
+        // Processing the file again changes nothing, so no backup is made
+        process_mdx_files(vec![fixture.article()], &settings);
+        let backup_made_again = Path::new(&backup_path).exists();
+
+        assert_eq!(backup_content.expect("Backup should exist"), content);
+        assert_ne!(processed_content, content);
+        assert!(!backup_made_again);
+    }
+    #[test]
+    fn backup_with_custom_suffix() {
+        let fixture = ArticleFixture::new("backup_suffix_test", "Original\n");
+        let mdx_path = &fixture.path;
+        let settings = Settings {
+            backup: true,
+            backup_suffix: Some(".orig".to_string()),
+            ..Default::default()
+        };
+
+        let backed_up = backup_file(mdx_path, &settings);
+        let backup_content = fs::read_to_string(format!("{}.orig", mdx_path));
+        let no_backup = backup_file(mdx_path, &Settings::default());
+        let default_backup_exists = Path::new(&format!("{}.bak", mdx_path)).exists();
+
+        assert!(backed_up.is_ok());
+        assert_eq!(backup_content.expect("Backup should exist"), "Original\n");
+        assert!(no_backup.is_ok());
+        assert!(!default_backup_exists);
+    }
+}
+
+#[cfg(test)]
+mod tests_process_report {
+    use super::*;
+
+    #[test]
+    fn appended_content() {
+        let file_change = compare_contents("a.mdx", "# Title\nText\n", "# Title\nText\n\n## Bibliography\n");
+        assert_eq!(
+            file_change,
+            FileChange {
+                path: "a.mdx".to_string(),
+                bytes_added: 17,
+                bytes_removed: 0,
+                lines_added: 2,
+                lines_removed: 0,
+            }
+        );
+    }
+    #[test]
+    fn replaced_line() {
+        let file_change = compare_contents("a.mdx", "a\nb\nc\n", "a\nB2\nc\n");
+        assert_eq!(file_change.lines_added, 1);
+        assert_eq!(file_change.lines_removed, 1);
+        assert_eq!(file_change.bytes_added, 3);
+        assert_eq!(file_change.bytes_removed, 2);
+    }
+    #[test]
+    fn report_totals() {
+        let report = ProcessReport {
+            files: vec![
+                compare_contents("a.mdx", "a\n", "a\nb\n"),
+                compare_contents("b.mdx", "a\n", "a\nbc\nd\n"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(report.total_lines_added(), 3);
+        assert_eq!(report.total_bytes_added(), 7);
+        assert!(report
+            .to_string()
+            .ends_with("Total: 2 files, +3 -0 lines, +7 -0 bytes"));
+    }
+    #[test]
+    fn outcome_totals() {
+        let outcome = InserterOutcome {
+            total_articles: 3,
+            total_articles_processed: 2,
+            total_bibliographies_inserted: 2,
+            total_authors_inserted: 1,
+            total_notes_headings_inserted: 1,
+            total_empty_payloads: 1,
+        };
+        assert_eq!(
+            outcome.to_string(),
+            "Total articles processed: 2/3. Inserted 2 bibliographies, 1 authors, and 1 notes headings. 1 were empty payloads"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_clean {
+    use super::*;
+
+    const BODY: &str = "---\ntitle: Test\n---\n\nSome text (Hegel 2010, 61).[^1]\n\n[^1]: A note.\n";
+
+    #[test]
+    fn strip_all_sections() {
+        let processed = format!(
+            "{}\n\n## Bibliography\n\n<div className=\"text-sm\">\n- Entry.\n</div>\n\n**Authors**  \nJane Doe\n\n**Editors**  \nJohn Doe\n\n**Notes**\n",
+            BODY
+        );
+        assert_eq!(strip_injected_content(&processed, &Settings::default()), BODY);
+    }
+    #[test]
+    fn strip_some_sections_with_crlf() {
+        let body = BODY.replace('\n', "\r\n");
+        let processed = format!("{}\r\n\r\n**Contributors**  \r\nJane Doe\r\n\r\n**Notes**\r\n", body);
+        assert_eq!(strip_injected_content(&processed, &Settings::default()), body);
+    }
+    #[test]
+    fn body_without_injected_sections_is_kept() {
+        assert_eq!(strip_injected_content(BODY, &Settings::default()), BODY);
+        let body = "Text\n\n**Notes**\n\nMy own notes.\n";
+        assert_eq!(strip_injected_content(body, &Settings::default()), body);
+    }
+    #[test]
+    fn strip_custom_bibliography_heading() {
+        let settings = Settings {
+            bibliography_heading: Some("References".to_string()),
+            bibliography_heading_level: Some(3),
+            ..Default::default()
+        };
+        let processed = format!("{}\n\n### References\n\n<section>\n- Entry.\n</section>\n\n**Notes**\n", BODY);
+        assert_eq!(strip_injected_content(&processed, &settings), BODY);
+        // The default heading is then no longer recognized as injected
+        let processed = format!("{}\n\n## Bibliography\n\n<div>\n- Entry.\n</div>\n", BODY);
+        assert_eq!(strip_injected_content(&processed, &settings), processed);
+    }
+}
+
+#[cfg(test)]
+mod tests_append_payload {
+    use super::*;
+
+    const FRONTMATTER: &str = "---\ntitle: Test\nslug: test-article\nlastUpdated: 2024-05-01\ntags: [logic]\n---\n";
+    const PAYLOAD: &str = "**Authors**  \nJane Doe\n";
+
+    #[test]
+    fn frontmatter_is_kept_as_it_is() {
+        let content = format!("{}\nSome text.\n", FRONTMATTER);
+        let updated = append_payload(&content, PAYLOAD, &HashMap::new(), &vec![], &Settings::default());
+        assert!(updated.starts_with(&content));
+        assert_eq!(updated, format!("{}\n{}", content, PAYLOAD));
+    }
+    #[test]
+    fn frontmatter_is_kept_when_reprocessed() {
+        let content = format!("{}\nSome text.\n", FRONTMATTER);
+        let updated = append_payload(&content, PAYLOAD, &HashMap::new(), &vec![], &Settings::default());
+        assert_eq!(append_payload(&updated, PAYLOAD, &HashMap::new(), &vec![], &Settings::default()), updated);
+    }
+    #[test]
+    fn frontmatter_is_kept_with_crlf() {
+        let content = format!("{}\nSome text.\n", FRONTMATTER).replace('\n', "\r\n");
+        let updated = append_payload(&content, PAYLOAD, &HashMap::new(), &vec![], &Settings::default());
+        assert!(updated.starts_with(&content));
+    }
+    #[test]
+    fn bibliography_only_replaces_the_bibliography_in_place() {
+        let content = format!("{}\nSome text.\n", FRONTMATTER);
+        let bibliography = "\n## Bibliography\n\n<div>\n- Old entry.\n</div>\n";
+        let processed = format!("{}\n{}\n**Authors**  \nJane Doe\n\n**Notes**\n", content, bibliography);
+        let settings = Settings {
+            bibliography_only: true,
+            ..Default::default()
+        };
+        let payload = "\n## Bibliography\n\n<div>\n- New entry.\n</div>\n";
+        assert_eq!(
+            append_payload(&processed, payload, &HashMap::new(), &vec![], &settings),
+            processed.replace("Old entry", "New entry")
+        );
+    }
+    #[test]
+    fn empty_payload_removes_earlier_sections() {
+        let content = format!("{}\nSome text.\n", FRONTMATTER);
+        let updated = append_payload(&content, PAYLOAD, &HashMap::new(), &vec![], &Settings::default());
+        assert_eq!(append_payload(&updated, "", &HashMap::new(), &vec![], &Settings::default()), content);
+        assert_eq!(append_payload(&content, "", &HashMap::new(), &vec![], &Settings::default()), content);
+    }
+}
+
+#[cfg(test)]
+mod tests_bibliography {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    const HEGEL_BIB: &str = "@book{hegel2010logic,
+        title = {The Science of Logic},
+        author = {Hegel, G.W.F.},
+        year = {2010},
+        publisher = {Cambridge University Press},
+        address = {Cambridge}
+    }";
+
+    #[test]
+    fn default_wrapper() {
+        let bibliography =
+            generate_mdx_bibliography(parse_entries(HEGEL_BIB), &Settings::default(), &mut Vec::new());
+        assert_eq!(
+            bibliography,
+            "\n## Bibliography\n\n<div className=\"text-sm\">\n\
+            - Hegel, G.W.F. 2010. _The Science of Logic_. Cambridge: Cambridge University Press.\n\
+            </div>\n"
+        );
+    }
+    #[test]
+    fn doi_and_ellipsis_title_are_kept_intact() {
+        let bib = "@book{hegel2010thinking,
+            title = {Thinking... Otherwise},
+            author = {Hegel, G.W.F.},
+            year = {2010},
+            publisher = {Cambridge University Press},
+            address = {Cambridge},
+            doi = {10.1000/xyz..123}
+        }";
+        let bibliography =
+            generate_mdx_bibliography(parse_entries(bib), &Settings::default(), &mut Vec::new());
+        assert!(bibliography.contains("- Hegel, G.W.F. 2010. _Thinking... Otherwise_. "));
+        assert!(bibliography.contains("https://doi.org/10.1000/xyz..123."));
+    }
+    #[test]
+    fn no_double_periods_after_initials_or_abbreviations() {
+        let bib = "@book{doe2020book,
+            title = {A Book},
+            author = {Doe, J.},
+            year = {2020},
+            publisher = {Acme Inc.},
+            address = {London}
+        }";
+        let bibliography =
+            generate_mdx_bibliography(parse_entries(bib), &Settings::default(), &mut Vec::new());
+        assert!(bibliography.contains("- Doe, J. 2020. _A Book_. London: Acme Inc.\n"));
+    }
+    #[test]
+    fn custom_components() {
+        let settings = Settings {
+            bibliography_open: Some("<Bibliography>".to_string()),
+            bibliography_close: Some("</Bibliography>".to_string()),
+            bibliography_entry_template: Some("<BibEntry>{entry}</BibEntry>".to_string()),
+            ..Default::default()
+        };
+        let bibliography =
+            generate_mdx_bibliography(parse_entries(HEGEL_BIB), &settings, &mut Vec::new());
+        assert_eq!(
+            bibliography,
+            "\n## Bibliography\n\n<Bibliography>\n\
+            <BibEntry>Hegel, G.W.F. 2010. _The Science of Logic_. Cambridge: Cambridge University Press.</BibEntry>\n\
+            </Bibliography>\n"
+        );
+    }
+    #[test]
+    fn markdown_output_format() {
+        let settings = Settings {
+            output_format: OutputFormat::Markdown,
+            ..Default::default()
+        };
+        let bibliography =
+            generate_mdx_bibliography(parse_entries(HEGEL_BIB), &settings, &mut Vec::new());
+        assert_eq!(
+            bibliography,
+            "\n## Bibliography\n\n\
+            - Hegel, G.W.F. 2010. _The Science of Logic_. Cambridge: Cambridge University Press.\n"
+        );
+        let processed = format!("Some text (Hegel 2010).\n{}", bibliography);
+        assert_eq!(strip_injected_content(&processed, &settings), "Some text (Hegel 2010).\n");
+    }
+    #[test]
+    fn custom_heading_and_wrapper() {
+        let settings = Settings {
+            bibliography_heading: Some("References".to_string()),
+            bibliography_heading_level: Some(3),
+            bibliography_open: Some("<section class=\"references\">".to_string()),
+            bibliography_close: Some("</section>".to_string()),
+            ..Default::default()
+        };
+        let bibliography =
+            generate_mdx_bibliography(parse_entries(HEGEL_BIB), &settings, &mut Vec::new());
+        assert_eq!(
+            bibliography,
+            "\n### References\n\n<section class=\"references\">\n\
+            - Hegel, G.W.F. 2010. _The Science of Logic_. Cambridge: Cambridge University Press.\n\
+            </section>\n"
+        );
+    }
+    #[test]
+    fn unsupported_entries_only() {
+        let entries = parse_entries(
+            "@patent{doe2020patent,
+                title = {A Patent},
+                author = {Doe, Jane},
+                year = {2020}
+            }",
+        );
+        assert_eq!(
+            generate_mdx_bibliography(entries.clone(), &Settings::default(), &mut Vec::new()),
+            ""
+        );
+
+        let settings = Settings {
+            unsupported_bibliography_placeholder: Some(
+                "_Some cited works could not be listed._".to_string(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_mdx_bibliography(entries, &settings, &mut Vec::new()),
+            "\n## Bibliography\n\n_Some cited works could not be listed._\n"
+        );
+    }
+    #[test]
+    fn bibliography_threshold() {
+        assert!(meets_bibliography_threshold(1, &Settings::default()));
+        let settings = Settings {
+            min_citations_for_bibliography: Some(3),
+            ..Default::default()
+        };
+        assert!(!meets_bibliography_threshold(2, &settings));
+        assert!(meets_bibliography_threshold(3, &settings));
+        assert!(meets_bibliography_threshold(4, &settings));
+    }
+    #[test]
+    fn braces_and_jsx_are_escaped() {
+        assert_eq!(
+            escape_jsx("Sets of the Form {x} and <Tags>"),
+            "Sets of the Form \\{x\\} and &lt;Tags>"
+        );
+    }
+    #[test]
+    fn jsx_in_titles_is_escaped_in_custom_entries() {
+        let settings = Settings {
+            bibliography_entry_template: Some("<BibEntry>{entry}</BibEntry>".to_string()),
+            ..Default::default()
+        };
+        let entries = parse_entries(
+            "@book{doe2020tags,
+                title = {On <Tags>},
+                author = {Doe, Jane},
+                year = {2020},
+                publisher = {Press},
+                address = {London}
+            }",
+        );
+        let bibliography = generate_mdx_bibliography(entries, &settings, &mut Vec::new());
+        assert!(bibliography.contains("<BibEntry>Doe, Jane. 2020. _On &lt;Tags>_. London: Press.</BibEntry>"));
+    }
+    #[test]
+    fn anchor_id_matches_citation_link() {
+        let settings = Settings {
+            link_citations: true,
+            ..Default::default()
+        };
+        let bibliography =
+            generate_mdx_bibliography(parse_entries(HEGEL_BIB), &settings, &mut Vec::new());
+        assert!(bibliography.contains(
+            "- <span id=\"cite-hegel2010logic\"></span>Hegel, G.W.F. 2010. _The Science of Logic_. "
+        ));
+        let entries = parse_entries(HEGEL_BIB);
+        let citation_index = validators::build_citation_index(&entries);
+        let linked_content =
+            validators::link_citations_in_mdx("Being (Hegel 2010, 61).", &citation_index, &entries, &settings);
+        assert_eq!(linked_content, "Being [(Hegel 2010, 61)](#cite-hegel2010logic).");
+    }
+    #[test]
+    fn anchor_ids_are_url_safe() {
+        assert_eq!(citation_anchor_id("James_Knappik_2024"), "cite-james_knappik_2024");
+        assert_eq!(citation_anchor_id("doe:2020/blog"), "cite-doe-2020-blog");
+    }
+}
@@ -1,111 +1,276 @@
-/*!
-Prepyrus is a tool for verifying and processing MDX files
-that contain citations in Chicago author-date style and certain metadata.
-
-⚠️ This tool is still in early development and API may frequently change.
-
-## Usage
-
-Add the crate to your `Cargo.toml` and use it as shown below:
-
-```toml
-[dependencies]
-prepyrus = "0.2"
-```
-
-Main API interface is the `Prepyrus` impl. Example usage:
-
-```rust
-use prepyrus::Prepyrus;
-
-fn main() {
-    let args = vec![
-        "_program_index".to_string(),
-        "tests/mocks/test.bib".to_string(), // bibliography file
-        "tests/mocks/data".to_string(), // target directory or .mdx file
-        "verify".to_string(), // mode
-        "tests/mocks/data/development.mdx".to_string(), // optional ignore paths, separate with commas if multiple
-    ];
-
-    let _ = run(args).unwrap_or_else(|e| {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
-    });
-
-    println!("===Prepyrus completed successfully!");
-}
-
-fn run(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
-    let config = Prepyrus::build_config(&args, None)?;
-    let all_entries = Prepyrus::get_all_bib_entries(&config.bib_file).unwrap();
-    let mdx_paths =
-        Prepyrus::get_mdx_paths(&config.target_path, Some(config.settings.ignore_paths))?;
-
-    // Phase 1: Verify MDX files
-    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries)?;
-
-    // Phase 2: Process MDX files (requires mode to be set to "process")
-    if config.mode == "process" {
-        Prepyrus::process(articles_file_data);
-    }
-
-    Ok(())
-}
-```
-
-`verify` mode only verifies the citations in the MDX files against the bibliography.
-
-**⚠️ NOTE: This mode modifies the MDX files.**
-
-`process` mode _additionally_ processes the MDX files by injecting bibliography and other details into the MDX files.
-
-## Description
-
-The tool is designed to work with MDX files that contain citations in Chicago author-date style. Examples:
-
-> "...nowhere on heaven or on earth is there anything which does not contain both being and nothing in itself" (Hegel 2010, 61).
-
-The tool parses and verifies the citations in the MDX files against a
-bibliography file in BibTeX format (using Biblatex).
-If the citations are valid, the tool processes the MDX files
-by adding a bibliography section at the end of the file.
-It also adds author, editor, and contributor from the MDX file metadata if available.
-Finally, it also adds a notes heading at the end if footnotes are present in the file.
-
-## Limitations
-
-The tool currently only supports citations in Chicago author-date style.
-Only book entries are currently supported (plans to support more types in the future).
-Only the following metadata fields are supported:
-
-- author
-- editor
-- contributor
-
-## Examples
-
-To see a working implementation of prepyrus, please visit the [sPhil repo](https://github.com/systemphil/sphil).
-
-## Acknowledgements
-
-Thanks to Typst's [biblatex](https://github.com/typst/biblatex) package for providing an awesome library for parsing BibTex files, the people behind serde and regex Rust crates and the Rust community!
-
-## License
-
-Apache-2.0
-*/
-
+//! Prepyrus is a tool for verifying and processing MDX files
+//! that contain citations in Chicago author-date style and certain metadata.
+//!
+//! ⚠️ This tool is still in early development and API may frequently change.
+//!
+//! ## Usage
+//!
+//! Add the crate to your `Cargo.toml` and use it as shown below:
+//!
+//! ```toml
+//! [dependencies]
+//! prepyrus = "0.2"
+//! ```
+//!
+//! For large sites, the `parallel` feature verifies the MDX files concurrently.
+//! The verified articles and any errors are reported in the same order as without it.
+//!
+//! ```toml
+//! [dependencies]
+//! prepyrus = { version = "0.2", features = ["parallel"] }
+//! ```
+//!
+//! Main API interface is the `Prepyrus` impl. Example usage:
+//!
+//! ```rust
+//! use prepyrus::Prepyrus;
+//!
+//! fn main() {
+//!     let args = vec![
+//!         "_program_index".to_string(),
+//!         "tests/mocks/test.bib".to_string(), // bibliography file, separate with commas if multiple
+//!         "tests/mocks/data".to_string(), // target directory or .mdx file
+//!         "verify".to_string(), // mode
+//!         "tests/mocks/data/development.mdx".to_string(), // optional ignore paths, separate with commas if multiple
+//!     ];
+//!
+//!     let _ = run(args).unwrap_or_else(|e| {
+//!         eprintln!("Error: {}", e);
+//!         std::process::exit(1);
+//!     });
+//!
+//!     println!("===Prepyrus completed successfully!");
+//! }
+//!
+//! fn run(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+//!     let config = Prepyrus::build_config(&args, None)?;
+//!     let all_entries = Prepyrus::get_all_bib_entries(&config.bib_file).unwrap();
+//!     let mdx_paths = Prepyrus::get_mdx_paths(
+//!         &config.target_path,
+//!         Some(config.settings.ignore_paths.clone()),
+//!         config.settings.extensions.clone(),
+//!         config.settings.skip_dirs.clone(),
+//!         config.settings.include_paths.clone(),
+//!     )?;
+//!
+//!     // Phase 1: Verify MDX files
+//!     let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &config.settings)?;
+//!
+//!     // Phase 2: Process MDX files (requires mode to be set to "process")
+//!     if config.mode == "process" {
+//!         Prepyrus::process(articles_file_data, &config.settings);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! `verify` mode only verifies the citations in the MDX files against the bibliography.
+//! It is read-only and never writes to the MDX files.
+//! `Prepyrus::verify_with_report` additionally returns the number of files scanned, articles verified,
+//! files skipped and citations matched, e.g. for CI to assert on.
+//!
+//! `process` mode _additionally_ processes the MDX files by injecting bibliography and other details into the MDX files.
+//!
+//! **⚠️ NOTE: `process` mode modifies the MDX files.**
+//!
+//! `clean` mode removes the bibliography, authors, editors, contributors and notes sections
+//! previously injected by `process`, leaving the original frontmatter and body intact, so that the files
+//! can be processed again from a clean state.
+//!
+//! Instead of walking the target directory, the MDX paths can be read from a newline-delimited
+//! manifest with `--files-from <manifest>`. The order of the manifest is kept, ignore paths still apply,
+//! and any listed file that does not exist is reported as an error.
+//!
+//! During `process`, `--generate-master-bib <path>` additionally writes a single master bibliography
+//! with every work cited across all articles to the given path.
+//!
+//! Similarly, `--emit-used-bib <path>` writes a BibTeX file with only the entries cited across all articles,
+//! e.g. to ship a minimal bibliography alongside exported content.
+//!
+//! Likewise, `--generate-tag-index <path>` writes an index of the articles grouped by the `tags`
+//! (or `keywords`) in their frontmatter, e.g. `tags: [logic, metaphysics]`.
+//!
+//! Articles marked with `draft: true` in their frontmatter are skipped unless `--include-drafts` is passed.
+//!
+//! `--lint-bib` warns about bibliography entries whose fields do not fit their type,
+//! e.g. an `@article` without a journal, or an `@article` with a publisher that is probably a `@book`.
+//!
+//! `--summary` prints the lines and bytes changed per file, and in total, after `process`.
+//!
+//! `--dry-run` previews `process` without writing anything: the full pipeline runs, but a unified diff
+//! of the changes to each article is printed instead of writing it, followed by the summary of changes.
+//! The metadata sidecars, master bibliography, used bibliography and tag index are not written either.
+//!
+//! `--backup` copies each file that `process` or `clean` is about to change to `<path>.bak` before overwriting it,
+//! e.g. in case of a run on the wrong directory. Another suffix can be given as `--backup=.orig`,
+//! or as `backup_suffix` in the settings along with `"backup": true`. Files left unchanged are not backed up.
+//!
+//! `--emit-metadata-json` writes the parsed frontmatter of each article to a `<path>.meta.json` sidecar
+//! during `process`, for build tools that would rather not parse the frontmatter themselves.
+//!
+//! `--bibliography-only` makes `process` append only the bibliography, leaving out the authors section
+//! and the notes heading, for a quick bibliography refresh. On an article processed before,
+//! only the bibliography is replaced, and the authors and notes sections of the earlier run are kept.
+//!
+//! `--link-citations` makes `process` attach an anchor to each bibliography entry, e.g. `<span id="cite-hegel2010logic"></span>`,
+//! and turn the citations of the article into links to them, e.g. `[(Hegel 2010, 61)](#cite-hegel2010logic)`.
+//! Linked citations are kept as they are when processing again, and `clean` does not unlink them.
+//!
+//! The bibliography heading and wrapper settings below can also be passed as flags, e.g.
+//! `--bibliography-heading References --bibliography-heading-level 3 --bibliography-open "<section>" --bibliography-close "</section>"`.
+//!
+//! By default, `verify` checks the citations of all files and reports every malformed, unmatched or
+//! ambiguous citation per file in one pass. `--fail-fast` stops at the first file with citation errors instead.
+//! A citation whose author matches a single entry but whose year does not, e.g. "(Kant 1781)" for an entry
+//! dated 1998, is reported as a year mismatch with the key and year of the entry, to catch transcription mistakes.
+//!
+//! `--strict-locators` additionally checks the locator after the year of each citation, which must be made of pages,
+//! page ranges or recognized locators, e.g. "61", "123–45", "xii", "chap. 3", "§ 12" or "n. 4", so that typos such as
+//! "(Hegel 2010, abc)" are reported. Multi-volume works are cited by volume and page, as in "(Hegel 2010, 1:61)".
+//!
+//! `--warn-unused` makes `verify` warn about bibliography entries that none of the verified articles cite,
+//! and `--strict-unused` fails verification on them instead, for bibliography hygiene.
+//! Both consider only the verified files, so target the whole site rather than a single file.
+//!
+//! ## Settings
+//!
+//! Settings are read from `prepyrus_settings.json`, which is created with defaults if missing.
+//! `ignore_paths` excludes every path containing one of its entries, e.g. `drafts/`, or matching one of its
+//! glob patterns, e.g. `tests/mocks/data/drafts/*.mdx` or `**/archive/**`, where `*` does not cross directories but `**` does.
+//! Entries with any of `*`, `?`, `[` or `{` are read as glob patterns, all others as plain substrings.
+//! `include_paths` (or `--include logic/,ethics/`) restricts verifying and processing to the paths
+//! matching its entries, e.g. `["**/logic/**"]`. Include paths are applied first, then the ignore paths
+//! remove from what is left, so a path must match an include path and no ignore path. All paths are included when unset.
+//!
+//! Besides `ignore_paths`, the following optional settings are supported:
+//!
+//! - `citation_density_threshold`: warn about paragraphs with more citations than this number.
+//! - `strict_parentheses`: check parentheses balance per paragraph and report the offending line,
+//!   instead of checking the balance across the whole file.
+//! - `extensions`: extensions of the files to verify and process, e.g. `["md", "mdx"]`. Defaults to `["mdx"]`.
+//!   Also set with `--extensions md,mdx`. A single-file target must have one of these extensions.
+//! - `skip_dirs`: names of the directories skipped when walking the target, e.g. `["contributing", "drafts"]`.
+//!   Defaults to `["contributing"]`. Also set with `--skip-dirs contributing,drafts`.
+//! - `output_format`: `jsx` (default) for MDX, or `markdown` for plain Markdown sites,
+//!   leaving out the JSX wrapper around the bibliography. Also set with `--output-format <jsx|markdown>`.
+//! - `bibliography_heading`, `bibliography_heading_level`: text and level (1 to 6) of the bibliography heading,
+//!   e.g. `References` and `3` for `### References`. Defaults to `Bibliography` and `2`.
+//!   `clean` recognizes the bibliography by the configured heading.
+//! - `bibliography_open`, `bibliography_close`: markup wrapping the bibliography list,
+//!   e.g. `<Bibliography>` and `</Bibliography>`. Defaults to `<div className="text-sm">` and `</div>`,
+//!   or to no wrapper for Markdown output.
+//! - `bibliography_entry_template`: markup for each entry, where `{entry}` is replaced by the entry,
+//!   e.g. `<BibEntry>{entry}</BibEntry>`. Defaults to `- {entry}`.
+//! - `min_citations_for_bibliography`: only add a bibliography to articles citing at least this many
+//!   distinct works, e.g. to leave it out of short articles with one or two citations. Citations are still verified.
+//! - `unsupported_bibliography_placeholder`: note shown under the bibliography heading when none
+//!   of the cited works have a supported entry type. When unset, no bibliography is added.
+//! - `allowed_keys`: bibliography keys articles may cite, e.g. only peer-reviewed sources.
+//!   When set, `verify` errors on any citation of another key, even if it exists in the bibliography.
+//! - `citation_regex_override`: regex replacing the built-in citation pattern, for house styles that deviate from it.
+//!   The citation must be captured without its parentheses, e.g. `Hegel 2010, 61`, in a capture group
+//!   named `citation`, or otherwise in the last capture group. E.g. for citations in brackets:
+//!   `"citation_regex_override": "\\[(?P<citation>[A-Z][^\\]]*? \\d{4}(?:, [^\\]]*)?)\\]"`.
+//!   An invalid pattern, or one without a capture group, is reported when the configuration is built.
+//! - `citation_prefixes`: signal phrases a parenthetical citation may open with, which are left out of the citation
+//!   before matching, e.g. `["see", "see also", "cf.", "e.g.,", "compare"]` for "(compare Hegel 2010)".
+//!   Defaults to `["see", "see also", "cf.", "e.g.,"]`.
+//! - `no_date_token`: token cited in place of the year of works without a date, e.g. `"o.J."`, and shown in the
+//!   year slot of their bibliography entry. Defaults to `"n.d."`, as in "(Hegel n.d.)" for an entry without a `date` or `year`.
+//! - `required_metadata`: frontmatter fields every file must have, e.g. `["title", "description", "isArticle", "authors"]`.
+//!   Each missing field is reported by name along with the path of the file. Defaults to `["title", "description", "isArticle"]`.
+//! - `lint_index_title`: warn when the `indexTitle` frontmatter differs from the `title` only by
+//!   whitespace or case, which is likely a typo. Intentionally different titles are allowed.
+//! - `derive_index_title`: give articles without an `indexTitle` in their frontmatter one derived from their `title`,
+//!   trimmed of extra whitespace, e.g. for the metadata written by `--emit-metadata-json`.
+//! - `classical_citations`: accept citations of classical works by Stephanus or Bekker numbers,
+//!   e.g. "(Plato, Republic 514a)" or "(Aristotle 1094a1)". These are matched against entries with
+//!   `entrysubtype = {classical}` by author and, if given, by short title or title. Such entries may omit the year.
+//! - `keep_trailing_newlines`: keep the trailing newlines of processed files as they are.
+//!   By default, processed files are written as UTF-8 ending with exactly one newline.
+//!
+//! ## Description
+//!
+//! The tool is designed to work with MDX files that contain citations in Chicago author-date style. Examples:
+//!
+//! > "...nowhere on heaven or on earth is there anything which does not contain both being and nothing in itself" (Hegel 2010, 61).
+//!
+//! Narrative citations, as in "As Hegel (2010, 61) notes...", are verified likewise.
+//! Works by two authors are cited as "(Marx and Engels 1848)", and by three or more as "(Marx et al. 1848)".
+//!
+//! The tool parses and verifies the citations in the MDX files against a
+//! bibliography file in BibTeX format (using Biblatex).
+//! If the citations are valid, the tool processes the MDX files
+//! by adding a bibliography section at the end of the file.
+//! It also adds author, editor, and contributor from the MDX file metadata if available.
+//! Finally, it also adds a notes heading at the end if footnotes are present in the file.
+//! Each file is written to a temporary `<path>.tmp` next to it, which then replaces the file,
+//! so that an interrupted run never leaves a half-written file behind.
+//! Files keep their line endings, e.g. the content injected into a file with CRLF line endings uses CRLF as well.
+//!
+//! The metadata is read from YAML frontmatter fenced by `---`, or from Hugo-style TOML frontmatter fenced by `+++`,
+//! e.g. `isArticle = true`, with the same fields either way.
+//! The `authors`, `editors` and `contributors` are either a pre-formatted string, e.g. `authors: Filip Niklas (2024)`,
+//! or a list of names or of objects with a `name` and an optional `url`, which are rendered one per line, e.g.
+//!
+//! ```yaml
+//! authors:
+//!   - Filip Niklas (2024)
+//!   - name: Jane Doe (2025)
+//!     url: https://example.org
+//! ```
+//!
+//! Any other keys of the frontmatter, e.g. `slug` or `lastUpdated`, are kept as they are:
+//! `process` leaves the frontmatter untouched,
+//! and `--emit-metadata-json` writes them along with the known fields.
+//!
+//! Author names are inverted in the bibliography ("Last, First") per Chicago style.
+//! Entries with `nameorder = {family-given}` keep their names in family-given order without inversion,
+//! as is conventional for Chinese, Japanese and Korean names.
+//!
+//! Works with a `pubstate`, e.g. `pubstate = {forthcoming}`, show their publication state ("Forthcoming.")
+//! in place of the year, and an `addendum` is appended at the end of the entry.
+//!
+//! ## Limitations
+//!
+//! The tool currently only supports citations in Chicago author-date style.
+//! Only book, article, report (`@report`, `@techreport`), book chapter (`@incollection`), conference paper (`@inproceedings`),
+//! thesis (`@thesis`, `@phdthesis`, `@mastersthesis`) and web resource (`@online`, `@misc`) entries are currently supported (plans to support more types in the future).
+//! Only the following metadata fields are supported:
+//!
+//! - author
+//! - editor
+//! - contributor
+//!
+//! ## Examples
+//!
+//! To see a working implementation of prepyrus, please visit the [sPhil repo](https://github.com/systemphil/sphil).
+//!
+//! ## Acknowledgements
+//!
+//! Thanks to Typst's [biblatex](https://github.com/typst/biblatex) package for providing an awesome library for parsing BibTex files, the people behind serde and regex Rust crates and the Rust community!
+//!
+//! ## License
+//!
+//! Apache-2.0
+
+pub mod errors;
 pub mod inserters;
 pub mod utils;
 pub mod validators;
 pub mod transformers;
+#[cfg(test)]
+mod test_utils;
 
 use std::io::Error;
 
-pub use crate::utils::Config;
+pub use crate::utils::{Config, OutputFormat, Settings};
 use biblatex::Entry;
 use utils::{BiblatexUtils, BibliographyError, LoadOrCreateSettingsTestMode, Utils};
-use validators::ArticleFileData;
+use errors::CitationError;
+use inserters::ProcessReport;
+use validators::{ArticleFileData, MatchOutcome, VerifyReport};
 
 /// Main API interface for the Prepyrus tool.
 /// It contains methods for building the configuration, retrieving bibliography entries,
@@ -116,10 +281,32 @@ pub struct Prepyrus {}
 impl Prepyrus {
     /// Build a configuration object from the command line arguments.
     /// - The first argument is the program index.
-    /// - The second argument is the path to the bibliography file.
+    /// - The second argument is the path to the bibliography file (separate with commas if multiple).
     /// - The third argument is the target path (directory or file).
-    /// - The fourth argument is the mode ("verify" or "process").
+    /// - The fourth argument is the mode ("verify", "process" or "clean").
     /// - The fifth argument is the optional ignore paths (separate with commas if multiple).
+    /// - `--files-from <manifest>` optionally reads the MDX paths from a newline-delimited manifest.
+    /// - `--generate-master-bib <path>` optionally writes a master bibliography during `process`.
+    /// - `--emit-used-bib <path>` optionally writes a BibTeX file with only the cited entries during `process`.
+    /// - `--generate-tag-index <path>` optionally writes an index of the articles by tag during `process`.
+    /// - `--include-drafts` optionally includes articles marked with `draft: true`.
+    /// - `--lint-bib` optionally warns about bibliography entries whose fields do not fit their type.
+    /// - `--summary` optionally prints the lines and bytes changed per file after `process`.
+    /// - `--emit-metadata-json` optionally writes the frontmatter of each article to `<path>.meta.json` during `process`.
+    /// - `--bibliography-only` optionally appends only the bibliography during `process`.
+    /// - `--fail-fast` optionally stops `verify` at the first file with citation errors.
+    /// - `--warn-unused` optionally warns about bibliography entries that are never cited,
+    ///   and `--strict-unused` fails `verify` on them.
+    /// - `--link-citations` optionally anchors the bibliography entries and links the citations to them.
+    /// - `--strict-locators` optionally checks that the locators of the citations are pages or recognized locators.
+    /// - `--dry-run` optionally previews `process`, printing a diff of the changes instead of writing them.
+    /// - `--backup[=suffix]` optionally copies each file to `<path>.bak`, or `<path><suffix>`, before overwriting it.
+    /// - `--bibliography-heading <text>` and `--bibliography-heading-level <1-6>` optionally set the bibliography heading.
+    /// - `--bibliography-open <markup>` and `--bibliography-close <markup>` optionally set the markup wrapping the bibliography.
+    /// - `--output-format <jsx|markdown>` optionally writes plain Markdown without JSX during `process`.
+    /// - `--extensions <md,mdx>` optionally sets the extensions of the files to verify and process.
+    /// - `--skip-dirs <dir,dir>` optionally sets the names of the directories skipped when walking the target.
+    /// - `--include <path,path>` optionally restricts the paths to those matching, before the ignore paths apply.
     /// - Optionally, a test mode can be passed to simulate the creation of a settings file.
     pub fn build_config(
         args: &Vec<String>,
@@ -128,32 +315,113 @@ impl Prepyrus {
         Utils::build_config(args, test_mode)
     }
 
-    /// Retrieve all bibliography entries from the bibliography file.
+    /// Retrieve all bibliography entries from the bibliography file,
+    /// or from several files separated with commas, merged into one bibliography.
+    /// Errors if a key is defined in more than one file.
     /// Returns a vector of `biblatex::Entry`.
     pub fn get_all_bib_entries(bib_file: &str) -> Result<Vec<biblatex::Entry>, BibliographyError> {
-        Ok(BiblatexUtils::retrieve_bibliography_entries(bib_file)?)
+        let bib_files = Utils::split_bib_files(bib_file);
+        Ok(BiblatexUtils::retrieve_merged_bibliography_entries(&bib_files)?)
+    }
+
+    /// Check the bibliography entries for fields that do not fit their type,
+    /// such as an `@article` without a journal. Returns a warning per suspicious entry.
+    pub fn lint_bib_entries(all_entries: &Vec<Entry>) -> Vec<String> {
+        BiblatexUtils::check_entries_consistency(all_entries)
     }
 
     /// Retrieve all MDX file paths from the target directory.
-    /// Optionally, ignore paths can be passed to exclude certain paths.
+    /// Optionally, ignore paths can be passed to exclude certain paths,
+    /// the extensions of the files to retrieve, e.g. `["md", "mdx"]`, which default to `["mdx"]`,
+    /// the names of the directories to skip, which default to `["contributing"]`,
+    /// and include paths to restrict the paths to before the ignore paths are applied.
     pub fn get_mdx_paths(
         target_path: &str,
         ignore_paths: Option<Vec<String>>,
+        extensions: Option<Vec<String>>,
+        skip_dirs: Option<Vec<String>>,
+        include_paths: Option<Vec<String>>,
     ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        Ok(Utils::extract_paths(target_path, ignore_paths)?)
+        Ok(Utils::extract_paths(target_path, ignore_paths, extensions, skip_dirs, include_paths)?)
+    }
+
+    /// Retrieve MDX file paths from a newline-delimited manifest file, in the order listed.
+    /// Include and ignore paths are still applied. Errors if any listed file does not exist.
+    pub fn get_mdx_paths_from_manifest(
+        manifest_path: &str,
+        ignore_paths: Option<Vec<String>>,
+        include_paths: Option<Vec<String>>,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Ok(Utils::extract_paths_from_manifest(manifest_path, ignore_paths, include_paths)?)
     }
 
     /// Verify the MDX files and their citations and match
-    /// them against the bibliography entries. Will throw if any of these fail.
+    /// them against the bibliography entries. Returns an error if any of these fail.
+    /// Optional lints, such as citation density, are enabled through the settings.
+    /// Only reads the MDX files and never writes to them.
     pub fn verify(
         mdx_paths: Vec<String>,
         all_entries: &Vec<Entry>,
+        settings: &Settings,
     ) -> Result<Vec<ArticleFileData>, Error> {
-        validators::verify_mdx_files(mdx_paths, &all_entries)
+        Ok(validators::verify_mdx_files(mdx_paths, &all_entries, settings)?.articles)
+    }
+
+    /// Verify the MDX files like `verify`, additionally returning the totals of the files scanned,
+    /// articles verified, files skipped and citations matched, e.g. for CI to assert on.
+    pub fn verify_with_report(
+        mdx_paths: Vec<String>,
+        all_entries: &Vec<Entry>,
+        settings: &Settings,
+    ) -> Result<VerifyReport, Error> {
+        validators::verify_mdx_files(mdx_paths, &all_entries, settings)
+    }
+
+    /// Generate a master bibliography with every work cited across all articles
+    /// and write it to the output path. Call before `process`, which consumes the articles.
+    pub fn gen_master_bibliography(
+        all_articles: &Vec<ArticleFileData>,
+        out_path: &str,
+        settings: &Settings,
+    ) -> Result<(), Error> {
+        inserters::generate_master_bibliography_to_file(all_articles, out_path, settings)
+    }
+
+    /// Write a BibTeX file with only the entries cited across all articles to the output path.
+    /// Call before `process`, which consumes the articles.
+    pub fn gen_used_bib(all_articles: &Vec<ArticleFileData>, out_path: &str) -> Result<(), Error> {
+        inserters::generate_used_bib_to_file(all_articles, out_path)
+    }
+
+    /// Generate an index of the articles grouped by their frontmatter tags
+    /// and write it to the output path. Call before `process`, which consumes the articles.
+    pub fn gen_tag_index(all_articles: &Vec<ArticleFileData>, out_path: &str) -> Result<(), Error> {
+        inserters::generate_tag_index_to_file(all_articles, out_path)
+    }
+
+    /// Check a single citation, such as "Hegel 2010, 61", against the bibliography entries,
+    /// e.g. to validate citations live while they are being written.
+    /// The outcome lists every matching entry, so ambiguous citations can be detected.
+    /// Signal phrases such as "see" are left out as configured in the settings.
+    pub fn check_citation(
+        citation: &str,
+        all_entries: &Vec<Entry>,
+        settings: &Settings,
+    ) -> Result<MatchOutcome, CitationError> {
+        validators::match_citation(citation, all_entries, settings)
+    }
+
+    /// Remove the content previously injected by `process` from the MDX files,
+    /// so that they can be processed again from a clean state. Returns the number of files cleaned.
+    /// The bibliography heading is recognized as configured in the settings.
+    pub fn clean(mdx_paths: Vec<String>, settings: &Settings) -> Result<usize, Error> {
+        inserters::clean_mdx_files(mdx_paths, settings)
     }
 
     /// Process the MDX files by injecting bibliography and other details into the MDX files.
-    pub fn process(all_articles: Vec<ArticleFileData>) {
-        inserters::process_mdx_files(all_articles)
+    /// The bibliography markup can be customized through the settings.
+    /// Returns a report of the totals inserted and of the lines and bytes changed per file.
+    pub fn process(all_articles: Vec<ArticleFileData>, settings: &Settings) -> ProcessReport {
+        inserters::process_mdx_files(all_articles, settings)
     }
 }
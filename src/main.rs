@@ -15,15 +15,53 @@ fn main() {
 fn run(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
     let config = Prepyrus::build_config(&args, None)?;
     let all_entries = Prepyrus::get_all_bib_entries(&config.bib_file).unwrap();
-    let mdx_paths =
-        Prepyrus::get_mdx_paths(&config.target_path, Some(config.settings.ignore_paths))?;
+    if config.settings.lint_bib {
+        for warning in Prepyrus::lint_bib_entries(&all_entries) {
+            println!("⚠ Bibliography: {}", warning);
+        }
+    }
+    let ignore_paths = Some(config.settings.ignore_paths.clone());
+    let include_paths = config.settings.include_paths.clone();
+    let mdx_paths = match &config.settings.files_from {
+        Some(manifest_path) => {
+            Prepyrus::get_mdx_paths_from_manifest(manifest_path, ignore_paths, include_paths)?
+        }
+        None => Prepyrus::get_mdx_paths(
+            &config.target_path,
+            ignore_paths,
+            config.settings.extensions.clone(),
+            config.settings.skip_dirs.clone(),
+            include_paths,
+        )?,
+    };
+
+    // Clean mode only removes previously injected content and needs no verification
+    if config.mode == "clean" {
+        Prepyrus::clean(mdx_paths, &config.settings)?;
+        return Ok(());
+    }
 
     // Phase 1: Verify MDX files
-    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries)?;
+    let verify_report = Prepyrus::verify_with_report(mdx_paths, &all_entries, &config.settings)?;
+    let articles_file_data = verify_report.articles;
 
     // Phase 2: Process MDX files (requires mode to be set to "process")
     if config.mode == "process" {
-        Prepyrus::process(articles_file_data);
+        // A dry run only previews the changes to the articles and writes no other files either
+        if config.settings.dry_run {
+            Prepyrus::process(articles_file_data, &config.settings);
+            return Ok(());
+        }
+        if let Some(out_path) = &config.settings.generate_master_bib {
+            Prepyrus::gen_master_bibliography(&articles_file_data, out_path, &config.settings)?;
+        }
+        if let Some(out_path) = &config.settings.emit_used_bib {
+            Prepyrus::gen_used_bib(&articles_file_data, out_path)?;
+        }
+        if let Some(out_path) = &config.settings.generate_tag_index {
+            Prepyrus::gen_tag_index(&articles_file_data, out_path)?;
+        }
+        Prepyrus::process(articles_file_data, &config.settings);
     }
 
     Ok(())
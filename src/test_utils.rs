@@ -0,0 +1,65 @@
+use crate::validators::ArticleFileData;
+use biblatex::{Bibliography, Entry};
+use std::fs;
+
+/// Parse the entries of a bibliography given as a string, e.g. a single `@book{...}`.
+pub fn parse_entries(bib: &str) -> Vec<Entry> {
+    Bibliography::parse(bib)
+        .expect("Failed to parse bibliography")
+        .into_vec()
+}
+
+/// Parse the first entry of a bibliography given as a string.
+pub fn parse_entry(bib: &str) -> Entry {
+    parse_entries(bib).remove(0)
+}
+
+/// Frontmatter of the articles written by `ArticleFixture`.
+const MOCK_FRONTMATTER: &str = "title: Test\ndescription: Test\nisArticle: true\nauthors: Jane Doe\n";
+
+/// A mock article written to `tests/mocks/<name>/article.mdx` for the tests processing files on disk.
+/// The directory is removed when the fixture is dropped.
+pub struct ArticleFixture {
+    pub dir: String,
+    pub path: String,
+}
+
+impl ArticleFixture {
+    pub fn new(name: &str, content: &str) -> Self {
+        let dir = format!("tests/mocks/{}", name);
+        fs::create_dir_all(&dir).expect("Failed to create directory");
+        let path = format!("{}/article.mdx", dir);
+        fs::write(&path, content).expect("Failed to write file");
+        ArticleFixture { dir, path }
+    }
+
+    /// The article as verified from the current content of the file, citing no entries.
+    pub fn article(&self) -> ArticleFileData {
+        let content = self.read();
+        ArticleFileData {
+            path: self.path.clone(),
+            metadata: serde_yaml::from_str(MOCK_FRONTMATTER).expect("Failed to parse frontmatter"),
+            markdown_content: content.clone(),
+            matched_citations: vec![],
+            full_file_content: content,
+        }
+    }
+
+    pub fn read(&self) -> String {
+        fs::read_to_string(&self.path).expect("Failed to read file")
+    }
+
+    /// Names of the files in the directory of the fixture.
+    pub fn file_names(&self) -> Vec<String> {
+        fs::read_dir(&self.dir)
+            .expect("Failed to read directory")
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect()
+    }
+}
+
+impl Drop for ArticleFixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
@@ -1,73 +1,318 @@
-use biblatex::{Entry, EntryType};
+use biblatex::{Entry, EntryType, RetrievalError};
 use utils::BiblatexUtils;
 
 use crate::utils;
 
+/// Token in the year slot of works without a date, in citations and in the bibliography.
+pub const DEFAULT_NO_DATE_TOKEN: &str = "n.d.";
+
+/// Entry left out of the bibliography because its type is not supported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedEntry {
+    pub key: String,
+    pub entry_type: String,
+}
+
 /// Transform a list of entries into a list of strings according to the Chicago bibliography style.
-pub fn entries_to_strings(entries: Vec<Entry>) -> Vec<String> {
+/// Entries of unsupported types are collected into `skipped_entries` to be reported by the caller.
+pub fn entries_to_strings(entries: Vec<Entry>, skipped_entries: &mut Vec<SkippedEntry>) -> Vec<String> {
+    entries_to_keyed_strings(entries, skipped_entries, DEFAULT_NO_DATE_TOKEN)
+        .into_iter()
+        .map(|(_, entry_string)| entry_string)
+        .collect()
+}
+
+/// Like `entries_to_strings`, but pairs each string with the key of its entry, e.g. to anchor it.
+/// Undated books and web resources get `no_date_token` in the year slot, e.g. "n.d.".
+pub fn entries_to_keyed_strings(
+    entries: Vec<Entry>,
+    skipped_entries: &mut Vec<SkippedEntry>,
+    no_date_token: &str,
+) -> Vec<(String, String)> {
     let sorted_entries = sort_entries(entries);
-    let mut strings_output: Vec<String> = Vec::new();
+    let mut strings_output: Vec<(String, String)> = Vec::new();
 
     for entry in sorted_entries {
-        match entry.entry_type {
-            EntryType::Book => {
-                strings_output.push(transform_book_entry(&entry));
+        let transformed_entry = match entry.entry_type {
+            EntryType::Book => transform_book_entry(&entry, no_date_token),
+            EntryType::Article => transform_article_entry(&entry),
+            EntryType::Report | EntryType::TechReport => transform_report_entry(&entry),
+            EntryType::InCollection => transform_incollection_entry(&entry),
+            EntryType::InProceedings => transform_inproceedings_entry(&entry),
+            EntryType::Thesis | EntryType::PhdThesis | EntryType::MastersThesis => {
+                transform_thesis_entry(&entry)
             }
-            EntryType::Article => {
-                strings_output.push(transform_article_entry(&entry))
+            EntryType::Online | EntryType::Misc => transform_online_entry(&entry, no_date_token),
+            _ => {
+                skipped_entries.push(SkippedEntry {
+                    key: entry.key.clone(),
+                    entry_type: format!("{:?}", entry.entry_type),
+                });
+                continue;
             }
-            _ => println!("Entry type not supported: {:?}", entry.entry_type),
+        };
+        match transformed_entry {
+            Ok(entry_string) => strings_output.push((entry.key.clone(), entry_string)),
+            Err(err) => eprintln!("Error transforming entry: {}", err),
         }
     }
 
     strings_output
 }
 
+/// Whether the entry type can be transformed into a bibliography entry.
+pub fn is_supported_entry_type(entry_type: &EntryType) -> bool {
+    matches!(
+        entry_type,
+        EntryType::Book
+            | EntryType::Article
+            | EntryType::Report
+            | EntryType::TechReport
+            | EntryType::InCollection
+            | EntryType::InProceedings
+            | EntryType::Thesis
+            | EntryType::PhdThesis
+            | EntryType::MastersThesis
+            | EntryType::Online
+            | EntryType::Misc
+    )
+}
+
 /// Transform a book entry into a string according to the Chicago bibliography style.
-fn transform_book_entry(entry: &Entry) -> String {
+fn transform_book_entry(entry: &Entry, no_date_token: &str) -> Result<String, String> {
     let mut book_string = String::new();
 
-    let author = entry.author().unwrap();
-    let title = extract_title(entry);
+    let author = entry.author().unwrap_or_default();
+    let title = extract_full_title(entry)?;
     let publisher = extract_publisher(entry);
     let address = extract_address(entry);
-    let year = extract_date(entry);
+    let pubstate = extract_pubstate(entry);
+    // Classical and unpublished works may go without a year, other undated works are marked as such
+    let year = match extract_date(entry) {
+        Ok(year) => Some(year.to_string()),
+        Err(_) if BiblatexUtils::is_classical(entry) || pubstate.is_some() => None,
+        Err(_) if has_no_date(entry) => Some(no_date_token.to_string()),
+        Err(err) => return Err(err),
+    };
+    let editors = extract_editors(entry);
     let translators = entry.translator().unwrap_or(Vec::new());
     let doi = entry.doi().unwrap_or("".to_string());
+    let url = entry.url().unwrap_or("".to_string());
+    let access_date = extract_access_date(entry);
+    let addendum = extract_addendum(entry);
 
-    add_authors(author, &mut book_string);
-    add_year(year, &mut book_string);
-    add_book_title(title, &mut book_string);
+    if !author.is_empty() {
+        add_authors(author, is_family_given_order(entry), &mut book_string);
+        add_year_or_pubstate(year, pubstate, &mut book_string);
+        add_book_title(title, &mut book_string);
+        add_editors(editors, &mut book_string);
+    } else if !editors.is_empty() {
+        // Edited volumes without an author lead with the editors in place of the author
+        add_editors_as_authors(editors, is_family_given_order(entry), &mut book_string);
+        add_year_or_pubstate(year, pubstate, &mut book_string);
+        add_book_title(title, &mut book_string);
+    } else {
+        // Anonymous works lead with the title in place of the author
+        add_book_title(title, &mut book_string);
+        add_year_or_pubstate(year, pubstate, &mut book_string);
+    }
     add_translators(translators, &mut book_string);
-    add_address_and_publisher(address, publisher, &mut book_string);
-    add_doi(doi, &mut book_string);
+    add_place_and_institution(address, publisher, &mut book_string);
+    // A DOI is preferred over a URL, e.g. for e-book editions
+    add_doi_or_url(doi, url, access_date, &mut book_string);
+    add_addendum(addendum, &mut book_string);
 
-    book_string.trim_end().to_string()
+    Ok(book_string.trim_end().to_string())
 }
 
 /// Transform an article entry into a string according to the Chicago bibliography style.
-fn transform_article_entry(entry: &Entry) -> String {
+/// Newspaper and magazine articles are dated by their full date instead of volume and issue.
+fn transform_article_entry(entry: &Entry) -> Result<String, String> {
     let mut article_string = String::new();
 
-    let author = entry.author().unwrap();
-    let title = extract_title(entry);
-    let journal = extract_journal(entry);
-    let volume = extract_volume(entry);
-    let number = extract_number(entry);
-    let pages = extract_pages(entry);
-    let year = extract_date(entry);
+    let author = entry.author().unwrap_or_default();
+    let title = extract_full_title(entry)?;
+    let journal = extract_journal(entry)?;
+    let pubstate = extract_pubstate(entry);
+    let full_date = extract_full_date(entry);
     let translators = entry.translator().unwrap_or(Vec::new());
     let doi = entry.doi().unwrap_or("".to_string());
+    let addendum = extract_addendum(entry);
 
-    add_authors(author, &mut article_string);
-    add_article_title(title, &mut article_string);
-    add_journal_volume_number_year_pages(
-        journal, volume, number, year, pages, &mut article_string,
-    );
+    if !author.is_empty() {
+        add_authors(author, is_family_given_order(entry), &mut article_string);
+    }
+    if let Some(pubstate) = pubstate {
+        // Forthcoming articles have no volume, issue or pages yet
+        let year = extract_date(entry).ok().map(|year| year.to_string());
+        add_year_or_pubstate(year, Some(pubstate), &mut article_string);
+        add_article_title(title, &mut article_string);
+        add_journal(journal, &mut article_string);
+        add_translators(translators, &mut article_string);
+        add_doi(doi, &mut article_string);
+        add_addendum(addendum, &mut article_string);
+        return Ok(article_string.trim_end().to_string());
+    }
+    let year = extract_date(entry)?;
+    match full_date {
+        Some(full_date) if is_periodical_subtype(entry) => {
+            add_year(year, &mut article_string);
+            add_article_title(title, &mut article_string);
+            add_journal_full_date(journal, full_date, &mut article_string);
+        }
+        _ => {
+            let volume = extract_volume(entry);
+            let number = extract_number(entry);
+            let pages = extract_optional_pages(entry);
+            add_article_title(title, &mut article_string);
+            add_journal_volume_number_year_pages(
+                journal, volume, number, year, pages, &mut article_string,
+            );
+        }
+    }
     add_translators(translators, &mut article_string);
     add_doi(doi, &mut article_string);
+    add_addendum(addendum, &mut article_string);
+
+    Ok(article_string.trim_end().to_string())
+}
+
+/// Transform a report entry into a string according to the Chicago bibliography style.
+/// Handles both `@report` and the BibTeX `@techreport`, the latter defaulting to "Technical Report".
+fn transform_report_entry(entry: &Entry) -> Result<String, String> {
+    let mut report_string = String::new();
+
+    let author = entry.author().unwrap_or_default();
+    let title = extract_full_title(entry)?;
+    let year = extract_date(entry)?;
+    let report_type = extract_report_type(entry);
+    let number = extract_optional_field(entry, "number");
+    let place = extract_optional_field(entry, "address")
+        .or_else(|| extract_optional_field(entry, "location"));
+    let institution = extract_optional_field(entry, "institution");
+    let doi = entry.doi().unwrap_or("".to_string());
+
+    if author.is_empty() {
+        add_book_title(title, &mut report_string);
+        add_year(year, &mut report_string);
+    } else {
+        add_authors(author, is_family_given_order(entry), &mut report_string);
+        add_year(year, &mut report_string);
+        add_book_title(title, &mut report_string);
+    }
+    add_report_type_and_number(report_type, number, &mut report_string);
+    add_place_and_institution(place, institution, &mut report_string);
+    add_doi(doi, &mut report_string);
+
+    Ok(report_string.trim_end().to_string())
+}
+
+/// Transform a chapter in an edited collection into a string according to the Chicago bibliography style,
+/// e.g. "Author. Year. "Chapter". In _Book Title_, edited by Editor, 1–20. Place: Publisher."
+fn transform_incollection_entry(entry: &Entry) -> Result<String, String> {
+    let booktitle = extract_optional_field(entry, "booktitle")
+        .ok_or_else(|| format!("No book title found for: {}", entry.key))?;
+    transform_contained_entry(entry, booktitle)
+}
+
+/// Transform a conference paper into a string according to the Chicago bibliography style,
+/// e.g. "Author. Year. "Paper". In _Proceedings Title_, edited by Editor, 1–20. Place: Publisher."
+/// Falls back to the event title for proceedings without a book title.
+fn transform_inproceedings_entry(entry: &Entry) -> Result<String, String> {
+    let proceedings_title = extract_optional_field(entry, "booktitle")
+        .or_else(|| extract_optional_field(entry, "eventtitle"))
+        .ok_or_else(|| format!("No proceedings title found for: {}", entry.key))?;
+    transform_contained_entry(entry, proceedings_title)
+}
+
+/// Transform a thesis into a string according to the Chicago bibliography style,
+/// e.g. "Author. Year. "Title". PhD diss., Institution."
+fn transform_thesis_entry(entry: &Entry) -> Result<String, String> {
+    let mut thesis_string = String::new();
 
-    article_string.trim_end().to_string()
+    let author = entry.author().unwrap_or_default();
+    let title = extract_full_title(entry)?;
+    let year = extract_date(entry)?;
+    let thesis_type = extract_thesis_type(entry);
+    let institution = extract_optional_field(entry, "institution")
+        .or_else(|| extract_optional_field(entry, "school"));
+    let doi = entry.doi().unwrap_or("".to_string());
+
+    if !author.is_empty() {
+        add_authors(author, is_family_given_order(entry), &mut thesis_string);
+    }
+    add_year(year, &mut thesis_string);
+    add_article_title(title, &mut thesis_string);
+    add_thesis_type_and_institution(thesis_type, institution, &mut thesis_string);
+    add_doi(doi, &mut thesis_string);
+
+    Ok(thesis_string.trim_end().to_string())
+}
+
+/// Transform a web resource into a string according to the Chicago bibliography style,
+/// e.g. "Author. Year. "Title". Website. Accessed May 1, 2024. https://example.org."
+/// Works without an author lead with the title, and the year is left out if unknown.
+/// Undated works with an author get `no_date_token` in the year slot, as they are cited by it.
+fn transform_online_entry(entry: &Entry, no_date_token: &str) -> Result<String, String> {
+    let mut online_string = String::new();
+
+    let author = entry.author().unwrap_or_default();
+    let title = extract_full_title(entry)?;
+    let year = match extract_date(entry) {
+        Ok(year) => Some(year.to_string()),
+        Err(_) if !author.is_empty() && has_no_date(entry) => Some(no_date_token.to_string()),
+        Err(_) => None,
+    };
+    let website = extract_optional_field(entry, "organization")
+        .or_else(|| extract_optional_field(entry, "publisher"))
+        .or_else(|| extract_optional_field(entry, "howpublished"));
+    let doi = entry.doi().unwrap_or("".to_string());
+    let url = entry.url().unwrap_or("".to_string());
+    let access_date = extract_access_date(entry);
+
+    if author.is_empty() {
+        add_article_title(title, &mut online_string);
+        add_year_or_pubstate(year, None, &mut online_string);
+    } else {
+        add_authors(author, is_family_given_order(entry), &mut online_string);
+        add_year_or_pubstate(year, None, &mut online_string);
+        add_article_title(title, &mut online_string);
+    }
+    if let Some(website) = website {
+        online_string.push_str(&format!("{} ", with_period(&escape_markdown(&website))));
+    }
+    add_doi_or_url(doi, url, access_date, &mut online_string);
+
+    Ok(online_string.trim_end().to_string())
+}
+
+/// Transform a work contained in another, such as a chapter or a conference paper,
+/// with the title of its container in italics.
+fn transform_contained_entry(entry: &Entry, booktitle: String) -> Result<String, String> {
+    let mut contained_string = String::new();
+
+    let author = entry.author().unwrap_or_default();
+    let title = extract_full_title(entry)?;
+    let year = extract_date(entry)?;
+    let editors = extract_editors(entry);
+    let pages = extract_optional_pages(entry);
+    let place = extract_optional_field(entry, "address")
+        .or_else(|| extract_optional_field(entry, "location"));
+    let publisher = extract_optional_field(entry, "publisher");
+    let translators = entry.translator().unwrap_or(Vec::new());
+    let doi = entry.doi().unwrap_or("".to_string());
+
+    if !author.is_empty() {
+        add_authors(author, is_family_given_order(entry), &mut contained_string);
+    }
+    add_year(year, &mut contained_string);
+    add_article_title(title, &mut contained_string);
+    add_collection(booktitle, editors, pages, &mut contained_string);
+    add_translators(translators, &mut contained_string);
+    add_place_and_institution(place, publisher, &mut contained_string);
+    add_doi(doi, &mut contained_string);
+
+    Ok(contained_string.trim_end().to_string())
 }
 
 /// Generate a string of a type of contributors. 
@@ -82,41 +327,159 @@ fn generate_contributors(
         contributors_str.push_str(&format!("{} by ", contributor_description));
         for (i, person) in contributors.iter().enumerate() {
             if i == contributors.len() - 1 {
-                contributors_str.push_str(&format!("and {} {}. ", person.given_name, person.name));
+                contributors_str
+                    .push_str(&format!("and {} ", with_period(&format_name(person, false))));
             } else {
-                contributors_str.push_str(&format!("{} {}, ", person.given_name, person.name));
+                contributors_str.push_str(&format!("{}, ", format_name(person, false)));
             }
         }
     } else if contributors.len() == 1 {
         contributors_str.push_str(&format!(
-            "{} by {} {}. ",
-            contributor_description, contributors[0].given_name, contributors[0].name
+            "{} by {} ",
+            contributor_description,
+            with_period(&format_name(&contributors[0], false))
         ));
     }
     contributors_str
 }
 
 /// Add authors to the target string. Handles the case when there are multiple authors.
-fn add_authors(author: Vec<biblatex::Person>, bib_html: &mut String) {
+/// When `family_given` is set, names are kept in family-given order without inversion.
+fn add_authors(author: Vec<biblatex::Person>, family_given: bool, bib_html: &mut String) {
+    let first_author = format_inverted_name(&author[0], family_given);
     if author.len() > 2 {
-        bib_html.push_str(&format!(
-            "{}, {} et al. ",
-            author[0].name, author[0].given_name
-        ));
+        bib_html.push_str(&format!("{} et al. ", first_author));
     } else if author.len() == 2 {
         // In Chicago style, when listing multiple authors in a bibliography entry, 
         // only the first author's name is inverted (i.e., "Last, First"). The second and subsequent 
         // authors' names are written in standard order (i.e., "First Last"). 
         // This rule helps differentiate the primary author from co-authors.
         bib_html.push_str(&format!(
-            "{}, {} and {} {}. ",
-            author[0].name, author[0].given_name, author[1].given_name, author[1].name
+            "{} and {} ",
+            first_author,
+            with_period(&format_name(&author[1], family_given))
         ));
     } else {
-        bib_html.push_str(&format!("{}, {}. ", author[0].name, author[0].given_name));
+        bib_html.push_str(&format!("{} ", with_period(&first_author)));
+    }
+}
+
+/// End a segment with a period, unless it already ends with one, e.g. the initials in "Hegel, G.W.F.",
+/// so that no double periods are produced.
+fn with_period(segment: &str) -> String {
+    if segment.ends_with('.') {
+        segment.to_string()
+    } else {
+        format!("{}.", segment)
+    }
+}
+
+/// Format a name for the lead position, i.e. "Last, First".
+/// Names in family-given order are not inverted, e.g. "Xi Jinping".
+/// Single names, e.g. "Plato", are kept as they are.
+fn format_inverted_name(person: &biblatex::Person, family_given: bool) -> String {
+    let name = escape_markdown(&person.name);
+    let given_name = escape_markdown(&person.given_name);
+    if given_name.is_empty() {
+        // Single names such as "Plato" or "Aristotle"
+        name
+    } else if family_given {
+        format!("{} {}", name, given_name)
+    } else {
+        format!("{}, {}", name, given_name)
+    }
+}
+
+/// Format a name in its natural order, i.e. "First Last", or "Last First" for family-given names.
+fn format_name(person: &biblatex::Person, family_given: bool) -> String {
+    let name = escape_markdown(&person.name);
+    let given_name = escape_markdown(&person.given_name);
+    if given_name.is_empty() {
+        name
+    } else if family_given {
+        format!("{} {}", name, given_name)
+    } else {
+        format!("{} {}", given_name, name)
+    }
+}
+
+/// Escape characters that markdown would read as emphasis or code,
+/// e.g. the asterisk in "C*-algebras".
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '_' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Whether the entry asks for its names to be kept in family-given order,
+/// as is conventional for Chinese, Japanese and Korean names.
+/// Set with `nameorder = {family-given}` on the entry.
+fn is_family_given_order(entry: &Entry) -> bool {
+    entry
+        .get("nameorder")
+        .map(|chunks| {
+            BiblatexUtils::extract_spanned_chunk(chunks)
+                .trim()
+                .eq_ignore_ascii_case("family-given")
+        })
+        .unwrap_or(false)
+}
+
+/// Join names in their natural order, e.g. "A B", "A B and C D" or "A B, C D, and E F".
+fn join_names(persons: &Vec<biblatex::Person>) -> String {
+    let names: Vec<String> = persons.iter().map(|person| format_name(person, false)).collect();
+    match names.len() {
+        0 => String::new(),
+        1 => names[0].clone(),
+        2 => format!("{} and {}", names[0], names[1]),
+        _ => format!(
+            "{}, and {}",
+            names[..names.len() - 1].join(", "),
+            names[names.len() - 1]
+        ),
+    }
+}
+
+/// Add the collection a chapter appeared in to the target string,
+/// with its editors and the page range of the chapter if they exist.
+fn add_collection(
+    booktitle: String,
+    editors: Vec<biblatex::Person>,
+    pages: Option<String>,
+    target_string: &mut String,
+) {
+    target_string.push_str(&format!("In _{}_", escape_markdown(&booktitle)));
+    if !editors.is_empty() {
+        target_string.push_str(&format!(", edited by {}", join_names(&editors)));
+    }
+    if let Some(pages) = pages {
+        target_string.push_str(&format!(", {}", pages));
+    }
+    target_string.push_str(". ");
+}
+
+/// Add editors to the target string if they exist, e.g. "Edited by First Last. ".
+fn add_editors(editors: Vec<biblatex::Person>, target_string: &mut String) {
+    let editors_mdx = generate_contributors(editors, "Edited".to_string());
+    if !editors_mdx.is_empty() {
+        target_string.push_str(&editors_mdx);
     }
 }
 
+/// Add editors in place of the authors to the target string, e.g. "Last, First, ed. ".
+fn add_editors_as_authors(editors: Vec<biblatex::Person>, family_given: bool, target_string: &mut String) {
+    let abbreviation = if editors.len() > 1 { "eds." } else { "ed." };
+    let mut editors_mdx = String::new();
+    add_authors(editors, family_given, &mut editors_mdx);
+    let editors_mdx = editors_mdx.trim_end().trim_end_matches('.');
+    target_string.push_str(&format!("{}, {} ", editors_mdx, abbreviation));
+}
+
 /// Add translators to the target string if they exist.
 fn add_translators(translators: Vec<biblatex::Person>, target_string: &mut String) {
     let translators_mdx = generate_contributors(translators, "Translated".to_string());
@@ -132,112 +495,1240 @@ fn add_doi(doi: String, target_string: &mut String) {
     }
 }
 
+/// Add the DOI to the target string if it exists, otherwise the URL with its access date.
+fn add_doi_or_url(doi: String, url: String, access_date: Option<String>, target_string: &mut String) {
+    if doi.is_empty() {
+        add_url(url, access_date, target_string);
+    } else {
+        add_doi(doi, target_string);
+    }
+}
+
+/// Add URL to the target string if it exists, preceded by the access date if known.
+fn add_url(url: String, access_date: Option<String>, target_string: &mut String) {
+    if url.is_empty() {
+        return;
+    }
+    if let Some(access_date) = access_date {
+        target_string.push_str(&format!("Accessed {}. ", access_date));
+    }
+    target_string.push_str(&format!("{}. ", url));
+}
+
 /// Add year to the target string.
 fn add_year(year: i32, target_string: &mut String) {
     target_string.push_str(&format!("{}. ", year));
 }
 
+/// Add the publication state in place of the year if it exists, e.g. "Forthcoming. ",
+/// otherwise the year if it exists. Chicago style drops the year of forthcoming works.
+/// The year may also be the no-date token, e.g. "n.d.", which already ends with a period.
+fn add_year_or_pubstate(year: Option<String>, pubstate: Option<String>, target_string: &mut String) {
+    match (pubstate, year) {
+        (Some(pubstate), _) => target_string.push_str(&format!("{} ", with_period(&pubstate))),
+        (None, Some(year)) => target_string.push_str(&format!("{} ", with_period(&year))),
+        (None, None) => {}
+    }
+}
+
+/// Add the addendum to the end of the target string if it exists.
+fn add_addendum(addendum: Option<String>, target_string: &mut String) {
+    if let Some(addendum) = addendum {
+        if !target_string.is_empty() && !target_string.ends_with(' ') {
+            target_string.push(' ');
+        }
+        target_string.push_str(&format!("{}. ", addendum.trim_end_matches('.')));
+    }
+}
+
 /// Add book title to the target string. Mainly used for books.
+/// No period follows titles that already end in sentence-ending punctuation.
 fn add_book_title(title: String, target_string: &mut String) {
-    target_string.push_str(&format!("_{}_. ", title));
+    let title = escape_markdown(&title);
+    if ends_with_sentence_punctuation(&title) {
+        target_string.push_str(&format!("_{}_ ", title));
+    } else {
+        target_string.push_str(&format!("_{}_. ", title));
+    }
 }
 
 /// Add article title to the target string. Mainly used for articles.
+/// No period follows titles that already end in sentence-ending punctuation.
 fn add_article_title(title: String, target_string: &mut String) {
-    target_string.push_str(&format!("\"{}\". ", title));
+    let title = escape_markdown(&title);
+    if ends_with_sentence_punctuation(&title) {
+        target_string.push_str(&format!("\"{}\" ", title));
+    } else {
+        target_string.push_str(&format!("\"{}\". ", title));
+    }
 }
 
-/// Add address and publisher to the target string. Mainly used for books.
-fn add_address_and_publisher(address: String, publisher: String, target_string: &mut String) {
-    target_string.push_str(&format!("{}: {}. ", address, publisher));
+/// Whether the text ends in a period, question mark or exclamation mark.
+fn ends_with_sentence_punctuation(text: &str) -> bool {
+    text.trim_end().ends_with(['.', '?', '!'])
 }
 
 /// Add journal, volume, number, year, and pages to the target string. Mainly used for articles.
+/// Volume, number and pages are left out when missing.
 fn add_journal_volume_number_year_pages(
     journal: String,
-    volume: i64,
-    number: String,
+    volume: Option<i64>,
+    number: Option<String>,
     year: i32,
-    pages: String,
+    pages: Option<String>,
     target_string: &mut String,
 ) {
-    target_string.push_str(&format!(
-        "_{}_ {}, no. {} ({}): {}. ",
-        journal, volume, number, year, pages
-    ));
+    target_string.push_str(&format!("_{}_", escape_markdown(&journal)));
+    if let Some(volume) = volume {
+        target_string.push_str(&format!(" {}", volume));
+    }
+    if let Some(number) = number {
+        target_string.push_str(&format!(", no. {}", number));
+    }
+    target_string.push_str(&format!(" ({})", year));
+    if let Some(pages) = pages {
+        target_string.push_str(&format!(": {}", pages));
+    }
+    target_string.push_str(". ");
 }
 
-/// Sort entries by author's last name.
+/// Add report type and number to the target string, e.g. "Technical Report No. 42. ".
+fn add_report_type_and_number(report_type: String, number: Option<String>, target_string: &mut String) {
+    match number {
+        Some(number) => {
+            target_string.push_str(&format!("{} No. {} ", report_type, with_period(&number)))
+        }
+        None => target_string.push_str(&format!("{} ", with_period(&report_type))),
+    }
+}
+
+/// Add thesis type and institution to the target string, e.g. "PhD diss., Institution. ".
+fn add_thesis_type_and_institution(
+    thesis_type: String,
+    institution: Option<String>,
+    target_string: &mut String,
+) {
+    match institution {
+        Some(institution) => target_string.push_str(&format!(
+            "{}, {} ",
+            thesis_type,
+            with_period(&escape_markdown(&institution))
+        )),
+        None if ends_with_sentence_punctuation(&thesis_type) => {
+            target_string.push_str(&format!("{} ", thesis_type))
+        }
+        None => target_string.push_str(&format!("{}. ", thesis_type)),
+    }
+}
+
+/// Add place and institution or publisher to the target string if they exist,
+/// e.g. "Cambridge: Cambridge University Press. ".
+fn add_place_and_institution(
+    place: Option<String>,
+    institution: Option<String>,
+    target_string: &mut String,
+) {
+    match (place, institution) {
+        (Some(place), Some(institution)) => target_string.push_str(&format!(
+            "{}: {} ",
+            escape_markdown(&place),
+            with_period(&escape_markdown(&institution))
+        )),
+        (None, Some(institution)) => {
+            target_string.push_str(&format!("{} ", with_period(&escape_markdown(&institution))))
+        }
+        (Some(place), None) => {
+            target_string.push_str(&format!("{} ", with_period(&escape_markdown(&place))))
+        }
+        (None, None) => {}
+    }
+}
+
+/// Add journal alone to the target string. Used for forthcoming articles.
+fn add_journal(journal: String, target_string: &mut String) {
+    target_string.push_str(&format!("_{}_. ", escape_markdown(&journal)));
+}
+
+/// Add journal and full date to the target string. Used for newspaper and magazine articles.
+fn add_journal_full_date(journal: String, full_date: String, target_string: &mut String) {
+    target_string.push_str(&format!("_{}_, {}. ", escape_markdown(&journal), full_date));
+}
+
+/// Whether the article appeared in a newspaper or magazine,
+/// set with `entrysubtype = {newspaper}` or `entrysubtype = {magazine}` on the entry.
+fn is_periodical_subtype(entry: &Entry) -> bool {
+    entry
+        .get("entrysubtype")
+        .map(|chunks| {
+            let subtype = BiblatexUtils::extract_spanned_chunk(chunks).to_lowercase();
+            subtype.trim() == "newspaper" || subtype.trim() == "magazine"
+        })
+        .unwrap_or(false)
+}
+
+/// Sort entries by author's last name, then given name, year and title,
+/// so that works by the same author are ordered by year and then title as in Chicago style.
+/// Anonymous works are sorted by their title.
 fn sort_entries(entries: Vec<Entry>) -> Vec<Entry> {
     let mut sorted_entries = entries.clone();
-    sorted_entries.sort_by(|a, b| {
-        let a_authors = a.author().unwrap_or_default();
-        let b_authors = b.author().unwrap_or_default();
-        
-        let a_last_name = a_authors.first()
-            .map(|p| p.name.clone().to_lowercase())
-            .unwrap_or_default();
-        let b_last_name = b_authors.first()
-            .map(|p| p.name.clone().to_lowercase())
-            .unwrap_or_default();
-        
-        a_last_name.cmp(&b_last_name)
-    });
+    sorted_entries.sort_by_cached_key(sort_key);
     sorted_entries
 }
 
-/// Title of the entry.
-fn extract_title(entry: &Entry) -> String {
-    let title_spanned = entry.title().unwrap();
-    let title = BiblatexUtils::extract_spanned_chunk(title_spanned);
-    title
+/// Key an entry is sorted by: last name, given name, year and title, all lowercased.
+/// Works without a year, e.g. forthcoming works, come after the dated works of an author.
+fn sort_key(entry: &Entry) -> (String, String, i32, String) {
+    let given_name = entry
+        .author()
+        .unwrap_or_default()
+        .first()
+        .map(|person| person.given_name.to_lowercase())
+        .unwrap_or_default();
+    let year = extract_date(entry).unwrap_or(i32::MAX);
+    let title = extract_title(entry).unwrap_or_default().to_lowercase();
+
+    (sort_name(entry), given_name, year, title)
 }
 
-/// Publisher of the entry.
-fn extract_publisher(entry: &Entry) -> String {
-    let publisher_spanned = entry.publisher().unwrap();
+/// Lowercased name an entry is sorted by: the first author's last name, or the title if anonymous.
+fn sort_name(entry: &Entry) -> String {
+    let authors = entry.author().unwrap_or_default();
+    match authors.first().or(BiblatexUtils::extract_editors(entry).first()) {
+        Some(person) => person.name.to_lowercase(),
+        None => BiblatexUtils::extract_anonymous_title(entry)
+            .unwrap_or_default()
+            .to_lowercase(),
+    }
+}
+
+/// Title of the entry. Falls back to the main title, book title and short title,
+/// in that order, for entries that do not store their display title under `title`.
+fn extract_title(entry: &Entry) -> Result<String, String> {
+    const TITLE_FIELDS: [&str; 4] = ["title", "maintitle", "booktitle", "shorttitle"];
+
+    TITLE_FIELDS
+        .iter()
+        .find_map(|field| entry.get(field))
+        .map(BiblatexUtils::extract_spanned_chunk)
+        .ok_or_else(|| format!("No title found for: {}", entry.key))
+}
+
+/// Title of the entry followed by its subtitle, if it has one, e.g. "Main Title: Subtitle".
+/// Per Chicago style, the subtitle follows a colon and starts with a capital letter.
+/// A title ending in a question or exclamation mark is followed by the subtitle without a colon.
+fn extract_full_title(entry: &Entry) -> Result<String, String> {
+    let title = extract_title(entry)?;
+    match extract_optional_field(entry, "subtitle") {
+        Some(subtitle) => Ok(join_title_and_subtitle(&title, &subtitle)),
+        None => Ok(title),
+    }
+}
+
+/// Join a title and its subtitle, capitalizing the first letter of the subtitle.
+fn join_title_and_subtitle(title: &str, subtitle: &str) -> String {
+    let title = title.trim_end().trim_end_matches(':').trim_end();
+    let mut subtitle_chars = subtitle.trim().chars();
+    let subtitle: String = match subtitle_chars.next() {
+        Some(first) => first.to_uppercase().chain(subtitle_chars).collect(),
+        None => return title.to_string(),
+    };
+    if title.ends_with(['?', '!']) {
+        format!("{} {}", title, subtitle)
+    } else {
+        format!("{}: {}", title, subtitle)
+    }
+}
+
+/// Publisher of the entry, if it has one.
+fn extract_publisher(entry: &Entry) -> Option<String> {
+    let publisher_spanned = entry.publisher().ok()?;
     let publisher = BiblatexUtils::extract_publisher(&publisher_spanned);
-    publisher
+    if publisher.trim().is_empty() {
+        None
+    } else {
+        Some(publisher)
+    }
+}
+
+/// Address of the publisher, if it has one. Falls back to the biblatex `location` field.
+fn extract_address(entry: &Entry) -> Option<String> {
+    extract_optional_field(entry, "address").or_else(|| extract_optional_field(entry, "location"))
 }
 
-/// Address of the publisher.
-fn extract_address(entry: &Entry) -> String {
-    let address_spanned = entry.address().unwrap();
-    let address = BiblatexUtils::extract_spanned_chunk(address_spanned);
-    address
+/// Whether the entry has no date at all, as opposed to a date that cannot be parsed.
+fn has_no_date(entry: &Entry) -> bool {
+    matches!(entry.date(), Err(RetrievalError::Missing(_)))
 }
 
 /// Year of entry.
-fn extract_date(entry: &Entry) -> i32 {
-    let date = entry.date().unwrap();
-    let year = BiblatexUtils::extract_year_from_date(&date, entry.key.clone()).unwrap();
-    year
+fn extract_date(entry: &Entry) -> Result<i32, String> {
+    let date = entry
+        .date()
+        .map_err(|_| format!("Unable to retrieve year for: {}", entry.key))?;
+    BiblatexUtils::extract_year_from_date(&date, entry.key.clone())
+}
+
+/// Full date of entry, e.g. "March 3, 2020", if it has month precision.
+fn extract_full_date(entry: &Entry) -> Option<String> {
+    let date = entry.date().ok()?;
+    BiblatexUtils::extract_full_date(&date)
+}
+
+/// Publication state of the entry, e.g. "Forthcoming" for `pubstate = {forthcoming}`.
+/// The standard biblatex keys are spelled out, other values are capitalized as they are.
+fn extract_pubstate(entry: &Entry) -> Option<String> {
+    let pubstate = BiblatexUtils::extract_spanned_chunk(entry.get("pubstate")?);
+    let pubstate = pubstate.trim();
+    let described = match pubstate.to_lowercase().as_str() {
+        "" => return None,
+        "forthcoming" => "Forthcoming".to_string(),
+        "inpress" => "In press".to_string(),
+        "inpreparation" => "In preparation".to_string(),
+        "submitted" => "Submitted".to_string(),
+        "prepublished" => "Prepublished".to_string(),
+        _ => {
+            let mut chars = pubstate.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => return None,
+            }
+        }
+    };
+    Some(described)
+}
+
+/// Type of the report, e.g. "White Paper". Defaults to "Technical Report" for `@techreport`
+/// and to "Report" otherwise. The biblatex keys `techreport` and `resreport` are spelled out.
+fn extract_report_type(entry: &Entry) -> String {
+    match extract_optional_field(entry, "type") {
+        Some(report_type) => match report_type.to_lowercase().as_str() {
+            "techreport" => "Technical Report".to_string(),
+            "resreport" => "Research Report".to_string(),
+            _ => report_type,
+        },
+        None if entry.entry_type == EntryType::TechReport => "Technical Report".to_string(),
+        None => "Report".to_string(),
+    }
+}
+
+/// Type of the thesis, e.g. "PhD diss." or "Master's thesis". The biblatex keys `phdthesis`
+/// and `mathesis` are spelled out, and `@phdthesis` and `@mastersthesis` imply their type.
+fn extract_thesis_type(entry: &Entry) -> String {
+    match extract_optional_field(entry, "type") {
+        Some(thesis_type) => match thesis_type.to_lowercase().as_str() {
+            "phdthesis" => "PhD diss.".to_string(),
+            "mathesis" | "mastersthesis" => "Master's thesis".to_string(),
+            _ => thesis_type,
+        },
+        None => match entry.entry_type {
+            EntryType::PhdThesis => "PhD diss.".to_string(),
+            EntryType::MastersThesis => "Master's thesis".to_string(),
+            _ => "Thesis".to_string(),
+        },
+    }
+}
+
+/// Value of a field that may be missing, trimmed. Empty values count as missing.
+fn extract_optional_field(entry: &Entry, field: &str) -> Option<String> {
+    let value = BiblatexUtils::extract_spanned_chunk(entry.get(field)?);
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// Editors of the entry, of any editor type.
+fn extract_editors(entry: &Entry) -> Vec<biblatex::Person> {
+    BiblatexUtils::extract_editors(entry)
+}
+
+/// Page range of the entry if it has one, e.g. "1–20".
+fn extract_optional_pages(entry: &Entry) -> Option<String> {
+    let pages = BiblatexUtils::extract_pages(&entry.pages().ok()?);
+    if pages.is_empty() {
+        None
+    } else {
+        Some(pages)
+    }
+}
+
+/// Addendum of the entry, printed at the end of the entry.
+fn extract_addendum(entry: &Entry) -> Option<String> {
+    extract_optional_field(entry, "addendum")
+}
+
+/// Date the URL of the entry was accessed, e.g. "May 1, 2024", from `urldate`.
+fn extract_access_date(entry: &Entry) -> Option<String> {
+    let date = entry.url_date().ok()?;
+    BiblatexUtils::extract_full_date(&date)
 }
 
 /// Name of the journal of the article.
-fn extract_journal(entry: &Entry) -> String {
-    let journal_spanned = entry.journal().unwrap();
+fn extract_journal(entry: &Entry) -> Result<String, String> {
+    let journal_spanned = entry
+        .journal()
+        .map_err(|_| format!("No journal found for: {}", entry.key))?;
     let journal = BiblatexUtils::extract_spanned_chunk(&journal_spanned);
-    journal
+    Ok(journal)
 }
 
-/// Volume of the journal.
-fn extract_volume(entry: &Entry) -> i64 {
-    let volume_permissive = entry.volume().unwrap();
+/// Volume of the journal, if it has one.
+fn extract_volume(entry: &Entry) -> Option<i64> {
+    let volume_permissive = entry.volume().ok()?;
     let volume = BiblatexUtils::extract_volume(&volume_permissive);
-    volume
+    Some(volume)
 }
 
-/// Number of the journal.
-fn extract_number(entry: &Entry) -> String {
-    let number_spanned = entry.number().unwrap();
-    let number = BiblatexUtils::extract_spanned_chunk(&number_spanned);
-    number
+/// Number of the journal, if it has one.
+fn extract_number(entry: &Entry) -> Option<String> {
+    extract_optional_field(entry, "number")
 }
 
-/// Pages of the article.
-fn extract_pages(entry: &Entry) -> String {
-    let pages_permissive = entry.pages().unwrap();
-    let pages = BiblatexUtils::extract_pages(&pages_permissive);
-    pages
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests_authors {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn inverted_author_name() {
+        let entries = parse_entries(
+            "@book{xi2014governance,
+                title = {The Governance of China},
+                author = {Xi, Jinping},
+                year = {2014},
+                publisher = {Foreign Languages Press},
+                address = {Beijing}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Xi, Jinping. 2014. _The Governance of China_. Beijing: Foreign Languages Press."]
+        );
+    }
+    #[test]
+    fn family_given_author_name_is_not_inverted() {
+        let entries = parse_entries(
+            "@book{xi2014governance,
+                title = {The Governance of China},
+                author = {Xi, Jinping},
+                nameorder = {family-given},
+                year = {2014},
+                publisher = {Foreign Languages Press},
+                address = {Beijing}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Xi Jinping. 2014. _The Governance of China_. Beijing: Foreign Languages Press."]
+        );
+    }
+    #[test]
+    fn family_given_co_author_names() {
+        let entries = parse_entries(
+            "@book{murakami2009,
+                title = {Absolutely on Music},
+                author = {Murakami, Haruki and Ozawa, Seiji},
+                nameorder = {family-given},
+                year = {2016},
+                publisher = {Knopf},
+                address = {New York}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Murakami Haruki and Ozawa Seiji. 2016. _Absolutely on Music_. New York: Knopf."]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_full_dates {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn newspaper_article_with_full_date() {
+        let entries = parse_entries(
+            "@article{smith2020news,
+                title = {Philosophy Returns to the Newsroom},
+                author = {Smith, John},
+                journal = {New York Times},
+                entrysubtype = {newspaper},
+                date = {2020-03-03}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Smith, John. 2020. \"Philosophy Returns to the Newsroom\". _New York Times_, March 3, 2020."]
+        );
+    }
+    #[test]
+    fn magazine_article_with_month() {
+        let entries = parse_entries(
+            "@article{doe2021mag,
+                title = {Reading Hegel Today},
+                author = {Doe, Jane},
+                journal = {The Atlantic},
+                entrysubtype = {magazine},
+                date = {2021-11}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. 2021. \"Reading Hegel Today\". _The Atlantic_, November 2021."]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_anonymous_works {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn anonymous_book_leads_with_title() {
+        let entries = parse_entries(
+            "@book{beowulf2000,
+                title = {Beowulf: A New Verse Translation},
+                shorttitle = {Beowulf},
+                year = {2000},
+                publisher = {W. W. Norton},
+                address = {New York}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["_Beowulf: A New Verse Translation_. 2000. New York: W. W. Norton."]
+        );
+    }
+    #[test]
+    fn anonymous_book_is_sorted_by_title() {
+        let entries = parse_entries(
+            "@book{kant1998critique,
+                title = {Critique of Pure Reason},
+                author = {Kant, Immanuel},
+                year = {1998},
+                publisher = {Cambridge University Press},
+                address = {Cambridge}
+            }
+            @book{beowulf2000,
+                title = {Beowulf},
+                year = {2000},
+                publisher = {W. W. Norton},
+                address = {New York}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert!(strings[0].starts_with("_Beowulf_"));
+        assert!(strings[1].starts_with("Kant, Immanuel"));
+    }
+}
+
+#[cfg(test)]
+mod tests_subtitles {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn book_with_subtitle() {
+        let entries = parse_entries(
+            "@book{pippin1989idealism,
+                title = {Hegel's Idealism},
+                subtitle = {the Satisfactions of Self-Consciousness},
+                author = {Pippin, Robert B.},
+                year = {1989}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Pippin, Robert B. 1989. _Hegel's Idealism: The Satisfactions of Self-Consciousness_."]
+        );
+    }
+    #[test]
+    fn book_without_subtitle_has_no_stray_colon() {
+        let entries = parse_entries(
+            "@book{pippin1989idealism, title = {Hegel's Idealism}, author = {Pippin, Robert B.}, year = {1989}}",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(strings, vec!["Pippin, Robert B. 1989. _Hegel's Idealism_."]);
+    }
+    #[test]
+    fn join_titles_and_subtitles() {
+        assert_eq!(join_title_and_subtitle("Main Title", "subtitle"), "Main Title: Subtitle");
+        assert_eq!(join_title_and_subtitle("Main Title:", "Subtitle"), "Main Title: Subtitle");
+        assert_eq!(join_title_and_subtitle("What Is Logic?", "an essay"), "What Is Logic? An essay");
+        assert_eq!(join_title_and_subtitle("Main Title", " "), "Main Title");
+    }
+}
+
+#[cfg(test)]
+mod tests_titles {
+    use super::*;
+    use biblatex::Bibliography;
+    use crate::test_utils::parse_entry;
+
+    #[test]
+    fn title() {
+        let entry = parse_entry("@book{a, title = {Title}, maintitle = {Main}, shorttitle = {Short}}");
+        assert_eq!(extract_title(&entry), Ok("Title".to_string()));
+    }
+    #[test]
+    fn title_with_math_chunk() {
+        let entries = Bibliography::parse(
+            "@book{doe2020phi, title = {The $\\phi$ of Spirit}, author = {Doe, Jane}, year = {2020}}",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec();
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(strings, vec!["Doe, Jane. 2020. _The $\\phi$ of Spirit_."]);
+    }
+    #[test]
+    fn maintitle_fallback() {
+        let entry = parse_entry("@book{a, maintitle = {Main}, booktitle = {Book}, shorttitle = {Short}}");
+        assert_eq!(extract_title(&entry), Ok("Main".to_string()));
+    }
+    #[test]
+    fn booktitle_fallback() {
+        let entry = parse_entry("@book{a, booktitle = {Book}, shorttitle = {Short}}");
+        assert_eq!(extract_title(&entry), Ok("Book".to_string()));
+    }
+    #[test]
+    fn shorttitle_fallback() {
+        let entry = parse_entry("@book{a, shorttitle = {Short}}");
+        assert_eq!(extract_title(&entry), Ok("Short".to_string()));
+    }
+    #[test]
+    fn missing_title_is_an_error() {
+        let entry = parse_entry("@book{untitled, author = {Doe, Jane}}");
+        assert_eq!(
+            extract_title(&entry),
+            Err("No title found for: untitled".to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_classical_works {
+    use super::*;
+    use biblatex::Bibliography;
+
+    #[test]
+    fn classical_work_without_year() {
+        let entries = Bibliography::parse(
+            "@book{plato1997republic,
+                title = {Republic},
+                author = {Plato},
+                entrysubtype = {classical},
+                publisher = {Hackett},
+                address = {Indianapolis}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec();
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(strings, vec!["Plato. _Republic_. Indianapolis: Hackett."]);
+    }
+}
+
+#[cfg(test)]
+mod tests_pubstate_and_addendum {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn forthcoming_book_with_addendum() {
+        let entries = parse_entries(
+            "@book{doe2025logic,
+                title = {Logic and Life},
+                author = {Doe, Jane},
+                pubstate = {forthcoming},
+                addendum = {Manuscript on file with the author},
+                publisher = {Oxford University Press},
+                address = {Oxford}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. Forthcoming. _Logic and Life_. Oxford: Oxford University Press. Manuscript on file with the author."]
+        );
+    }
+    #[test]
+    fn forthcoming_book_drops_year() {
+        let entries = parse_entries(
+            "@book{doe2025logic,
+                title = {Logic and Life},
+                author = {Doe, Jane},
+                year = {2025},
+                pubstate = {inpress},
+                publisher = {Oxford University Press},
+                address = {Oxford}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. In press. _Logic and Life_. Oxford: Oxford University Press."]
+        );
+    }
+    #[test]
+    fn undated_book_gets_no_date_token() {
+        let bib = "@book{doe_undated,
+            title = {Logic and Life},
+            author = {Doe, Jane},
+            publisher = {Oxford University Press},
+            address = {Oxford}
+        }";
+        let strings = entries_to_strings(parse_entries(bib), &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. n.d. _Logic and Life_. Oxford: Oxford University Press."]
+        );
+        let keyed_strings = entries_to_keyed_strings(parse_entries(bib), &mut Vec::new(), "o.J.");
+        assert_eq!(keyed_strings[0].1, "Doe, Jane. o.J. _Logic and Life_. Oxford: Oxford University Press.");
+    }
+    #[test]
+    fn forthcoming_article() {
+        let entries = parse_entries(
+            "@article{doe2025dialectic,
+                title = {Dialectic Revisited},
+                author = {Doe, Jane},
+                journal = {Hegel Bulletin},
+                pubstate = {forthcoming}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. Forthcoming. \"Dialectic Revisited\". _Hegel Bulletin_."]
+        );
+    }
+    #[test]
+    fn addendum_follows_doi() {
+        let mut target = String::from("Doe, Jane. 2020. _Title_. London: Press.  https://doi.org/10.1/x.");
+        add_addendum(Some("Reprint of the 1900 edition.".to_string()), &mut target);
+        assert_eq!(
+            target.trim_end(),
+            "Doe, Jane. 2020. _Title_. London: Press.  https://doi.org/10.1/x. Reprint of the 1900 edition."
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_title_punctuation {
+    use super::*;
+
+    #[test]
+    fn title_ending_in_a_word() {
+        let mut target = String::new();
+        add_book_title("The Science of Logic".to_string(), &mut target);
+        assert_eq!(target, "_The Science of Logic_. ");
+    }
+    #[test]
+    fn title_ending_in_a_period() {
+        let mut target = String::new();
+        add_book_title("Logic, Etc.".to_string(), &mut target);
+        assert_eq!(target, "_Logic, Etc._ ");
+    }
+    #[test]
+    fn article_title_ending_in_a_question_mark() {
+        let mut target = String::new();
+        add_article_title("What Is Dialectic?".to_string(), &mut target);
+        assert_eq!(target, "\"What Is Dialectic?\" ");
+    }
+    #[test]
+    fn entry_with_title_ending_in_a_period() {
+        let entries = biblatex::Bibliography::parse(
+            "@book{doe2020logic,
+                title = {Logic, Etc.},
+                author = {Doe, Jane},
+                year = {2020},
+                publisher = {Press},
+                address = {London}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec();
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(strings, vec!["Doe, Jane. 2020. _Logic, Etc._ London: Press."]);
+    }
+}
+
+#[cfg(test)]
+mod tests_sorting {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    fn sorted_keys(entries: Vec<Entry>) -> Vec<String> {
+        sort_entries(entries)
+            .into_iter()
+            .map(|entry| entry.key)
+            .collect()
+    }
+
+    #[test]
+    fn same_surname_is_sorted_by_given_name() {
+        let entries = parse_entries(
+            "@book{smith2010zeno, title = {Zeno}, author = {Smith, Zoe}, year = {2010}}
+            @book{smith2015adam, title = {Adam}, author = {Smith, Adam}, year = {2015}}",
+        );
+        assert_eq!(sorted_keys(entries), vec!["smith2015adam", "smith2010zeno"]);
+    }
+    #[test]
+    fn same_author_is_sorted_by_year_then_title() {
+        let entries = parse_entries(
+            "@book{hegel2018phenomenology, title = {The Phenomenology of Spirit}, author = {Hegel, G.W.F.}, year = {2018}}
+            @book{hegel2010logic, title = {The Science of Logic}, author = {Hegel, G.W.F.}, year = {2010}}
+            @book{hegel2010encyclopaedia, title = {Encyclopaedia of the Philosophical Sciences}, author = {Hegel, G.W.F.}, year = {2010}}",
+        );
+        assert_eq!(
+            sorted_keys(entries),
+            vec!["hegel2010encyclopaedia", "hegel2010logic", "hegel2018phenomenology"]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_reports {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn report_with_type_and_number() {
+        let entries = parse_entries(
+            "@report{doe2019policy,
+                title = {Philosophy in Public Policy},
+                author = {Doe, Jane},
+                year = {2019},
+                type = {White Paper},
+                number = {12},
+                institution = {Institute for Public Philosophy},
+                location = {London}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. 2019. _Philosophy in Public Policy_. White Paper No. 12. London: Institute for Public Philosophy."]
+        );
+    }
+    #[test]
+    fn techreport_without_number() {
+        let entries = parse_entries(
+            "@techreport{smith2021logic,
+                title = {Formal Logic in Practice},
+                author = {Smith, John},
+                year = {2021},
+                institution = {MIT},
+                address = {Cambridge, MA}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Smith, John. 2021. _Formal Logic in Practice_. Technical Report. Cambridge, MA: MIT."]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_markdown_escaping {
+    use super::*;
+    use biblatex::Bibliography;
+
+    #[test]
+    fn escape_emphasis_and_code() {
+        assert_eq!(escape_markdown("C*-algebras"), "C\\*-algebras");
+        assert_eq!(escape_markdown("snake_case and `code`"), "snake\\_case and \\`code\\`");
+        assert_eq!(escape_markdown("Plain title"), "Plain title");
+    }
+    #[test]
+    fn asterisk_in_title() {
+        let entries = Bibliography::parse(
+            "@book{doe2020algebras,
+                title = {An Introduction to C*-Algebras},
+                author = {Doe, Jane},
+                year = {2020},
+                publisher = {Springer},
+                address = {Berlin}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec();
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. 2020. _An Introduction to C\\*-Algebras_. Berlin: Springer."]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_skipped_entries {
+    use super::*;
+    use biblatex::Bibliography;
+
+    #[test]
+    fn unsupported_entries_are_collected() {
+        let entries = Bibliography::parse(
+            "@patent{doe2020patent, title = {A Patent}, author = {Doe, Jane}, year = {2020}}
+            @book{hegel2010logic,
+                title = {The Science of Logic},
+                author = {Hegel, G.W.F.},
+                year = {2010},
+                publisher = {Cambridge University Press},
+                address = {Cambridge}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec();
+        let mut skipped_entries = Vec::new();
+        let strings = entries_to_strings(entries, &mut skipped_entries);
+        assert_eq!(strings.len(), 1);
+        assert_eq!(
+            skipped_entries,
+            vec![SkippedEntry {
+                key: "doe2020patent".to_string(),
+                entry_type: "Patent".to_string(),
+            }]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_urls {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn ebook_with_url_and_access_date() {
+        let entries = parse_entries(
+            "@book{hegel2010logic,
+                title = {The Science of Logic},
+                author = {Hegel, G.W.F.},
+                year = {2010},
+                publisher = {Cambridge University Press},
+                address = {Cambridge},
+                url = {https://example.org/logic},
+                urldate = {2024-05-01}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Hegel, G.W.F. 2010. _The Science of Logic_. Cambridge: Cambridge University Press. Accessed May 1, 2024. https://example.org/logic."]
+        );
+    }
+    #[test]
+    fn doi_is_preferred_over_url() {
+        let entries = parse_entries(
+            "@book{hegel2010logic,
+                title = {The Science of Logic},
+                author = {Hegel, G.W.F.},
+                year = {2010},
+                publisher = {Cambridge University Press},
+                address = {Cambridge},
+                doi = {10.1017/CBO9780511780240},
+                url = {https://example.org/logic}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert!(strings[0].contains("https://doi.org/10.1017/CBO9780511780240"));
+        assert!(!strings[0].contains("https://example.org/logic"));
+    }
+}
+
+#[cfg(test)]
+mod tests_incollection {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn chapter_in_edited_collection() {
+        let entries = parse_entries(
+            "@incollection{pippin2008logic,
+                title = {Hegel's Logic},
+                author = {Pippin, Robert},
+                year = {2008},
+                booktitle = {The Cambridge Companion to Hegel},
+                editor = {Beiser, Frederick and Houlgate, Stephen},
+                pages = {100--120},
+                publisher = {Cambridge University Press},
+                address = {Cambridge}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Pippin, Robert. 2008. \"Hegel's Logic\". In _The Cambridge Companion to Hegel_, edited by Frederick Beiser and Stephen Houlgate, 100–120. Cambridge: Cambridge University Press."]
+        );
+    }
+    #[test]
+    fn chapter_without_editors_or_pages() {
+        let entries = parse_entries(
+            "@incollection{doe2020chapter,
+                title = {A Chapter},
+                author = {Doe, Jane},
+                year = {2020},
+                booktitle = {A Collection},
+                publisher = {Press},
+                address = {London}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. 2020. \"A Chapter\". In _A Collection_. London: Press."]
+        );
+    }
+    #[test]
+    fn join_three_names() {
+        let entries = parse_entries("@book{a, author = {A, Ann and B, Bob and C, Cid}}");
+        let persons = entries[0].author().unwrap();
+        assert_eq!(join_names(&persons), "Ann A, Bob B, and Cid C");
+    }
+}
+
+#[cfg(test)]
+mod tests_inproceedings {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn conference_paper() {
+        let entries = parse_entries(
+            "@inproceedings{doe2021dialectic,
+                title = {Dialectic and Computation},
+                author = {Doe, Jane},
+                year = {2021},
+                booktitle = {Proceedings of the Hegel Society},
+                editor = {Smith, John},
+                pages = {5--17},
+                publisher = {Hegel Society},
+                location = {Berlin}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. 2021. \"Dialectic and Computation\". In _Proceedings of the Hegel Society_, edited by John Smith, 5–17. Berlin: Hegel Society."]
+        );
+    }
+    #[test]
+    fn conference_paper_with_event_title() {
+        let entries = parse_entries(
+            "@inproceedings{doe2022logic,
+                title = {Logic Today},
+                author = {Doe, Jane},
+                year = {2022},
+                eventtitle = {International Hegel Congress}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. 2022. \"Logic Today\". In _International Hegel Congress_."]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_theses {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn thesis_with_explicit_type() {
+        let entries = parse_entries(
+            "@thesis{doe2019thesis,
+                title = {Negation in Hegel's Logic},
+                author = {Doe, Jane},
+                year = {2019},
+                type = {phdthesis},
+                institution = {University of Chicago}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. 2019. \"Negation in Hegel's Logic\". PhD diss., University of Chicago."]
+        );
+    }
+    #[test]
+    fn thesis_without_explicit_type() {
+        let entries = parse_entries(
+            "@mastersthesis{smith2020thesis,
+                title = {Measure and Quantity},
+                author = {Smith, John},
+                year = {2020},
+                school = {University of Warwick}
+            }
+            @thesis{roe2021thesis,
+                title = {Being and Nothing},
+                author = {Roe, Richard},
+                year = {2021}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec![
+                "Roe, Richard. 2021. \"Being and Nothing\". Thesis.",
+                "Smith, John. 2020. \"Measure and Quantity\". Master's thesis, University of Warwick.",
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_online {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn online_resource_with_access_date() {
+        let entries = parse_entries(
+            "@online{doe2023blog,
+                title = {Reading the Logic Together},
+                author = {Doe, Jane},
+                year = {2023},
+                organization = {sPhil},
+                url = {https://example.org/blog},
+                urldate = {2024-05-01}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. 2023. \"Reading the Logic Together\". sPhil. Accessed May 1, 2024. https://example.org/blog."]
+        );
+    }
+    #[test]
+    fn misc_resource_without_author() {
+        let entries = parse_entries(
+            "@misc{hegelsociety,
+                title = {The Hegel Society},
+                year = {2024},
+                url = {https://example.org}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["\"The Hegel Society\". 2024. https://example.org."]
+        );
+    }    #[test]
+    fn undated_online_resource_with_author() {
+        let entries = parse_entries(
+            "@online{doe_blog,
+                title = {Reading the Logic Together},
+                author = {Doe, Jane},
+                url = {https://example.org/blog}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Doe, Jane. n.d. \"Reading the Logic Together\". https://example.org/blog."]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_editors {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn book_with_author_and_editor() {
+        let entries = parse_entries(
+            "@book{hegel1991encyclopaedia,
+                title = {The Encyclopaedia Logic},
+                author = {Hegel, Georg},
+                editor = {Geraets, Theodore},
+                year = {1991},
+                publisher = {Hackett},
+                address = {Indianapolis}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec!["Hegel, Georg. 1991. _The Encyclopaedia Logic_. Edited by Theodore Geraets. Indianapolis: Hackett."]
+        );
+    }
+    #[test]
+    fn edited_volume_leads_with_editors() {
+        let entries = parse_entries(
+            "@book{beiser2008companion,
+                title = {The Cambridge Companion to Hegel},
+                editor = {Beiser, Frederick and Houlgate, Stephen},
+                year = {2008},
+                publisher = {Cambridge University Press},
+                address = {Cambridge}
+            }
+            @book{doe2010,
+                title = {Single Editor},
+                editor = {Doe, Jane},
+                year = {2010},
+                publisher = {Press},
+                address = {London}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(
+            strings,
+            vec![
+                "Beiser, Frederick and Stephen Houlgate, eds. 2008. _The Cambridge Companion to Hegel_. Cambridge: Cambridge University Press.",
+                "Doe, Jane, ed. 2010. _Single Editor_. London: Press.",
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_missing_fields {
+    use super::*;
+    use crate::test_utils::parse_entries;
+
+    #[test]
+    fn book_without_author_publisher_or_address() {
+        let entries = parse_entries("@book{untitled2020, title = {A Pamphlet}, year = {2020}}");
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(strings, vec!["_A Pamphlet_. 2020."]);
+    }
+    #[test]
+    fn article_without_volume_number_or_pages() {
+        let entries = parse_entries(
+            "@article{doe2020article,
+                title = {An Article},
+                author = {Doe, Jane},
+                journal = {Hegel Bulletin},
+                year = {2020}
+            }",
+        );
+        let strings = entries_to_strings(entries, &mut Vec::new());
+        assert_eq!(strings, vec!["Doe, Jane. \"An Article\". _Hegel Bulletin_ (2020)."]);
+    }
+    #[test]
+    fn article_without_journal_is_skipped() {
+        let entries = parse_entries(
+            "@article{doe2020article, title = {An Article}, author = {Doe, Jane}, year = {2020}}",
+        );
+        assert!(entries_to_strings(entries, &mut Vec::new()).is_empty());
+        let entry = parse_entries("@article{doe2020article, title = {An Article}}").remove(0);
+        assert_eq!(
+            extract_journal(&entry),
+            Err("No journal found for: doe2020article".to_string())
+        );
+    }
+}
@@ -1,4 +1,9 @@
-use biblatex::{Bibliography, Chunk, Date, DateValue, Entry, PermissiveType, Spanned};
+use biblatex::{
+    Bibliography, Chunk, Date, DateValue, Entry, EntryType, PermissiveType, Person, RawBibliography,
+    Spanned,
+};
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::{
     fs::{self, create_dir_all, File}, io::{self, Write}, ops::Range, path::Path
@@ -14,20 +19,107 @@ pub struct Utils;
 pub enum BibliographyError {
     IoError(std::io::Error),
     ParseError(biblatex::ParseError),
+    /// Keys defined more than once, within a bibliography file or across files.
+    DuplicateKeys(Vec<String>),
 }
 
 impl BiblatexUtils {
     /// Retrieve bibliography entries from a BibTeX file.
+    /// Errors with the duplicated keys if any key is defined more than once,
+    /// as the parsed bibliography would otherwise silently keep only one of the entries.
     pub fn retrieve_bibliography_entries(
         bibliography_path: &str,
     ) -> Result<Vec<Entry>, BibliographyError> {
         let bibliography_path =
             fs::read_to_string(bibliography_path).map_err(BibliographyError::IoError)?;
+        let raw_bibliography =
+            RawBibliography::parse(&bibliography_path).map_err(BibliographyError::ParseError)?;
+        let duplicate_keys = Self::find_duplicate_keys(&raw_bibliography);
+        if !duplicate_keys.is_empty() {
+            return Err(BibliographyError::DuplicateKeys(duplicate_keys));
+        }
         let bibliography =
             Bibliography::parse(&bibliography_path).map_err(BibliographyError::ParseError)?;
         Ok(bibliography.into_vec())
     }
 
+    /// Keys defined by more than one entry of the bibliography, in order of their first duplicate.
+    fn find_duplicate_keys(raw_bibliography: &RawBibliography) -> Vec<String> {
+        let mut keys: Vec<&str> = Vec::new();
+        let mut duplicate_keys: Vec<String> = Vec::new();
+        for entry in &raw_bibliography.entries {
+            let key = entry.v.key.v;
+            if keys.contains(&key) {
+                if !duplicate_keys.iter().any(|duplicate| duplicate == key) {
+                    duplicate_keys.push(key.to_string());
+                }
+            } else {
+                keys.push(key);
+            }
+        }
+        duplicate_keys
+    }
+
+    /// Retrieve the bibliography entries of several BibTeX files, merged into one bibliography,
+    /// e.g. for references split into a file per topic.
+    /// Errors with the duplicated keys if any key is defined in more than one file.
+    pub fn retrieve_merged_bibliography_entries(
+        bibliography_paths: &Vec<String>,
+    ) -> Result<Vec<Entry>, BibliographyError> {
+        let mut merged_entries: Vec<Entry> = Vec::new();
+        let mut duplicate_keys: Vec<String> = Vec::new();
+        for bibliography_path in bibliography_paths {
+            let entries = Self::retrieve_bibliography_entries(bibliography_path)?;
+            for entry in &entries {
+                if merged_entries.iter().any(|merged| merged.key == entry.key)
+                    && !duplicate_keys.contains(&entry.key)
+                {
+                    duplicate_keys.push(entry.key.clone());
+                }
+            }
+            merged_entries.extend(entries);
+        }
+        if !duplicate_keys.is_empty() {
+            return Err(BibliographyError::DuplicateKeys(duplicate_keys));
+        }
+        Ok(merged_entries)
+    }
+
+    /// Check that the entries have the fields their type calls for, and flag entries
+    /// that look mistyped, e.g. an `@article` with a publisher but no journal.
+    /// Returns a warning per suspicious entry, naming its key.
+    pub fn check_entries_consistency(entries: &Vec<Entry>) -> Vec<String> {
+        let has_field = |entry: &Entry, fields: &[&str]| fields.iter().any(|f| entry.get(f).is_some());
+        let mut warnings = Vec::new();
+
+        for entry in entries {
+            let has_journal = has_field(entry, &["journal", "journaltitle"]);
+            let has_publisher = has_field(entry, &["publisher"]);
+
+            match entry.entry_type {
+                EntryType::Article if !has_journal && has_publisher => warnings.push(format!(
+                    "{}: @article has a publisher but no journal, it is probably a @book",
+                    entry.key
+                )),
+                EntryType::Article if !has_journal => {
+                    warnings.push(format!("{}: @article has no journal", entry.key))
+                }
+                EntryType::Book if !has_publisher && has_journal => warnings.push(format!(
+                    "{}: @book has a journal but no publisher, it is probably an @article",
+                    entry.key
+                )),
+                EntryType::Book if !has_publisher => {
+                    warnings.push(format!("{}: @book has no publisher", entry.key))
+                }
+                _ => {}
+            }
+            if !has_field(entry, &["date", "year"]) {
+                warnings.push(format!("{}: entry has no date or year", entry.key));
+            }
+        }
+        warnings
+    }
+
     /// Extract the year from a date that is inside of a permissive type.
     pub fn extract_year_from_date(date: &PermissiveType<Date>, reference: String) -> Result<i32, String> {
         match date {
@@ -41,6 +133,55 @@ impl BiblatexUtils {
         }
     }
 
+    /// Extract the full date, e.g. "March 3, 2020", from a date that is inside of a permissive type.
+    /// Returns `None` when the date has no month, as only the year is known then.
+    pub fn extract_full_date(date: &PermissiveType<Date>) -> Option<String> {
+        const MONTHS: [&str; 12] = [
+            "January", "February", "March", "April", "May", "June", "July", "August",
+            "September", "October", "November", "December",
+        ];
+        let datetime = match date {
+            PermissiveType::Typed(date) => match date.value {
+                DateValue::At(datetime) => datetime,
+                DateValue::After(datetime) => datetime,
+                DateValue::Before(datetime) => datetime,
+                DateValue::Between(start, _end) => start,
+            },
+            _ => return None,
+        };
+        // Biblatex counts months and days starting at zero.
+        let month = MONTHS.get(datetime.month? as usize)?;
+        match datetime.day {
+            Some(day) => Some(format!("{} {}, {}", month, day + 1, datetime.year)),
+            None => Some(format!("{} {}", month, datetime.year)),
+        }
+    }
+
+    /// Editors of the entry, of any editor type, e.g. to stand in for the authors of an edited volume.
+    pub fn extract_editors(entry: &Entry) -> Vec<Person> {
+        entry
+            .editors()
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|(persons, _editor_type)| persons)
+            .collect()
+    }
+
+    /// Whether the entry is a classical work cited by Stephanus or Bekker numbers
+    /// rather than by year, set with `entrysubtype = {classical}` on the entry.
+    pub fn is_classical(entry: &Entry) -> bool {
+        entry
+            .get("entrysubtype")
+            .map(|chunks| Self::extract_spanned_chunk(chunks).trim().eq_ignore_ascii_case("classical"))
+            .unwrap_or(false)
+    }
+
+    /// Title used in place of the author for anonymous works, preferring the short title.
+    pub fn extract_anonymous_title(entry: &Entry) -> Option<String> {
+        let title = entry.get("shorttitle").or_else(|| entry.get("title"))?;
+        Some(Self::extract_spanned_chunk(title))
+    }
+
     /// Extract volume from a permissive type.
     pub fn extract_volume(volume: &PermissiveType<i64>) -> i64 {
         match volume {
@@ -49,20 +190,41 @@ impl BiblatexUtils {
         }
     }
 
-    /// Extract pages from a permissive type.
+    /// Extract pages from a permissive type, e.g. "12–34, 45–67".
+    /// Multiple ranges are separated by commas, and a range of a single page becomes that page.
     pub fn extract_pages(pages: &PermissiveType<Vec<Range<u32>>>) -> String {
         match pages {
-            PermissiveType::Typed(pages) => {
-                let mut pages_str = String::new();
-                for page in pages {
-                    pages_str.push_str(&format!("{}–{}", page.start, page.end));
-                }
-                pages_str
-            }
+            PermissiveType::Typed(pages) => pages
+                .iter()
+                .map(|page| Self::format_page_range(page.start, page.end))
+                .collect::<Vec<String>>()
+                .join(", "),
             _ => String::new(),
         }
     }
 
+    /// Format a page range with the second number abbreviated per Chicago style,
+    /// e.g. "3–10", "100–104", "101–8", "321–28" and "1496–500".
+    fn format_page_range(start: u32, end: u32) -> String {
+        if start == end {
+            return start.to_string();
+        }
+        let (start_digits, end_digits) = (start.to_string(), end.to_string());
+        // Numbers below 100, multiples of 100, and ranges crossing into more digits are kept in full
+        if end < start || start < 100 || start.is_multiple_of(100) || start_digits.len() != end_digits.len() {
+            return format!("{}–{}", start_digits, end_digits);
+        }
+        let common_prefix = start_digits
+            .chars()
+            .zip(end_digits.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        // 101 through 109 keep only the changed part, 110 through 199 at least two digits
+        let min_digits = if start % 100 < 10 { 1 } else { 2 };
+        let kept_digits = (end_digits.len() - common_prefix).max(min_digits);
+        format!("{}–{}", start_digits, &end_digits[end_digits.len() - kept_digits..])
+    }
+
     /// Use this to extract from a `Spanned<Chunk>` vector
     ///
     /// ```rust
@@ -87,13 +249,20 @@ impl BiblatexUtils {
     pub fn extract_spanned_chunk(spanned_chunk: &[Spanned<Chunk>]) -> String {
         spanned_chunk
             .iter()
-            .filter_map(|spanned_chunk| match spanned_chunk.v {
-                Chunk::Normal(ref s) => Some(s.clone()),
-                _ => None,
-            })
+            .map(|spanned_chunk| Self::render_chunk(&spanned_chunk.v))
             .collect()
     }
 
+    /// Render a chunk as text. Verbatim chunks, e.g. braced words in titles, are kept as they are,
+    /// and math chunks are wrapped in `$` to be rendered as inline math in MDX, e.g. `$\phi$`.
+    pub fn render_chunk(chunk: &Chunk) -> String {
+        match chunk {
+            Chunk::Normal(s) => s.clone(),
+            Chunk::Verbatim(s) => s.clone(),
+            Chunk::Math(s) => format!("${}$", s),
+        }
+    }
+
     /// Extract the publisher from a `Spanned<Chunk>` vector.
     pub fn extract_publisher(publisher_data: &Vec<Vec<Spanned<Chunk>>>) -> String {
         publisher_data
@@ -101,10 +270,7 @@ impl BiblatexUtils {
             .flat_map(|inner_vec| {
                 inner_vec
                     .iter()
-                    .filter_map(|spanned_chunk| match spanned_chunk.v {
-                        Chunk::Normal(ref s) => Some(s.clone()),
-                        _ => None,
-                    })
+                    .map(|spanned_chunk| Self::render_chunk(&spanned_chunk.v))
             })
             .collect()
     }
@@ -118,16 +284,160 @@ pub struct Config {
     pub settings: Settings,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Settings {
     #[serde(default)]
     pub ignore_paths: Vec<String>,
+    /// Paths to restrict verifying and processing to, matched like the ignore paths,
+    /// e.g. `["**/logic/**"]`. Applied before the ignore paths. All paths are included when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_paths: Option<Vec<String>>,
+    /// Extensions of the files to verify and process, e.g. `["md", "mdx"]`. Defaults to `["mdx"]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<String>>,
+    /// Names of the directories skipped when walking the target, e.g. `["contributing", "drafts"]`.
+    /// Defaults to `["contributing"]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_dirs: Option<Vec<String>>,
+    /// Path to a newline-delimited manifest of MDX files to use instead of walking the target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files_from: Option<String>,
+    /// Warn about paragraphs containing more citations than this threshold. Off when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citation_density_threshold: Option<usize>,
+    /// Check parentheses balance per paragraph instead of across the whole file.
+    #[serde(default)]
+    pub strict_parentheses: bool,
+    /// Text of the bibliography heading. Defaults to `Bibliography`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bibliography_heading: Option<String>,
+    /// Level of the bibliography heading, from 1 to 6. Defaults to 2, i.e. `## Bibliography`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bibliography_heading_level: Option<usize>,
+    /// Format of the injected content, MDX with JSX or plain Markdown. Defaults to JSX.
+    #[serde(default)]
+    pub output_format: OutputFormat,
+    /// Markup opening the bibliography list.
+    /// Defaults to `<div className="text-sm">` for JSX output and to none for Markdown output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bibliography_open: Option<String>,
+    /// Markup closing the bibliography list. Defaults to `</div>` for JSX output and to none for Markdown output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bibliography_close: Option<String>,
+    /// Template for each bibliography entry, where `{entry}` is replaced by the entry.
+    /// Defaults to `- {entry}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bibliography_entry_template: Option<String>,
+    /// Include articles marked with `draft: true` in their frontmatter.
+    #[serde(default)]
+    pub include_drafts: bool,
+    /// Warn about bibliography entries whose fields do not fit their type.
+    #[serde(default)]
+    pub lint_bib: bool,
+    /// Only add a bibliography to articles citing at least this many distinct works.
+    /// Citations are still verified either way. Always added when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_citations_for_bibliography: Option<usize>,
+    /// Note shown under the bibliography heading when none of the cited works
+    /// have a supported entry type. When unset, no bibliography is added in that case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsupported_bibliography_placeholder: Option<String>,
+    /// Bibliography keys articles may cite. When set, citing any other key is an error,
+    /// even if it exists in the bibliography.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_keys: Option<Vec<String>>,
+    /// Regex replacing the built-in citation pattern. The citation, e.g. "Hegel 2010, 61",
+    /// is taken from the capture group named `citation`, or otherwise the last capture group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citation_regex_override: Option<String>,
+    /// Signal phrases a parenthetical citation may open with, e.g. "cf.".
+    /// Defaults to "see", "see also", "cf." and "e.g.,".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citation_prefixes: Option<Vec<String>>,
+    /// Token cited in place of the year of works without a date, e.g. "o.J.". Defaults to "n.d.".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_date_token: Option<String>,
+    /// Warn when the `indexTitle` frontmatter differs from the `title` only by whitespace or case.
+    #[serde(default)]
+    pub lint_index_title: bool,
+    /// Derive the `indexTitle` of articles omitting it from their `title`, trimmed of extra whitespace.
+    #[serde(default)]
+    pub derive_index_title: bool,
+    /// Accept citations of classical works by Stephanus or Bekker numbers,
+    /// e.g. "(Plato, Republic 514a)" or "(Aristotle 1094a1)".
+    #[serde(default)]
+    pub classical_citations: bool,
+    /// Path to write a master bibliography of all cited works to during `process`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generate_master_bib: Option<String>,
+    /// Path to write a BibTeX file with only the cited entries to during `process`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emit_used_bib: Option<String>,
+    /// Path to write an index of the articles grouped by their tags to during `process`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generate_tag_index: Option<String>,
+    /// Print a summary of the lines and bytes changed per file after `process`.
+    #[serde(default)]
+    pub summary: bool,
+    /// Write the parsed frontmatter of each article to `<path>.meta.json` during `process`.
+    #[serde(default)]
+    pub emit_metadata_json: bool,
+    /// Keep the trailing newlines of processed files as they are,
+    /// instead of ending them with exactly one newline.
+    #[serde(default)]
+    pub keep_trailing_newlines: bool,
+    /// Only append the bibliography during `process`, leaving out the authors and notes heading.
+    /// The authors and notes sections of an earlier run are kept.
+    #[serde(default)]
+    pub bibliography_only: bool,
+    /// Stop `verify` at the first file with citation errors instead of reporting the errors of all files.
+    #[serde(default)]
+    pub fail_fast: bool,
+    /// Warn about bibliography entries that no article cites.
+    #[serde(default)]
+    pub warn_unused: bool,
+    /// Fail `verify` when any bibliography entry is not cited by any article.
+    #[serde(default)]
+    pub strict_unused: bool,
+    /// Anchor the bibliography entries and link the citations of an article to them in `process`.
+    #[serde(default)]
+    pub link_citations: bool,
+    /// Check that the locator of every citation is a page, a page range or a recognized locator.
+    #[serde(default)]
+    pub strict_locators: bool,
+    /// Frontmatter fields every file must have, e.g. `["title", "description", "isArticle", "authors"]`.
+    /// Defaults to `title`, `description` and `isArticle`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_metadata: Option<Vec<String>>,
+    /// Preview `process` without writing anything, printing a diff of the changes to each article instead.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Copy each file that `process` or `clean` changes to `<path><suffix>` before overwriting it.
+    #[serde(default)]
+    pub backup: bool,
+    /// Suffix of the copies made with `backup`, e.g. ".orig". Defaults to ".bak".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_suffix: Option<String>,
+}
+
+/// Format of the content injected by `process`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// MDX, with the bibliography wrapped in a JSX element.
+    #[default]
+    Jsx,
+    /// Plain Markdown without JSX, for ordinary Markdown sites.
+    Markdown,
 }
 
 pub enum LoadOrCreateSettingsTestMode {
     Test,
 }
 
+/// A `--flag` argument, with its value if it takes one.
+type Flag = (String, Option<String>);
+
 impl Utils {
     /// Load or create settings file. 
     /// If the file does not exist, it will be created with default settings.
@@ -138,14 +448,13 @@ impl Utils {
         if let Some(LoadOrCreateSettingsTestMode::Test) = test_mode {
             return Ok(Settings {
                 ignore_paths: vec!["tests/mocks/data/development.mdx".to_string()],
+                ..Default::default()
             });
         }
         if !std::path::Path::new(settings_path).exists() {
             create_dir_all(std::path::Path::new(settings_path).parent().unwrap())?;
 
-            let default_settings = Settings {
-                ignore_paths: Vec::new(),
-            };
+            let default_settings = Settings::default();
             let config_json = serde_json::to_string_pretty(&default_settings)?;
 
             let mut file = File::create(settings_path)?;
@@ -159,15 +468,221 @@ impl Utils {
     }
 
     /// Extract paths of MDX files from a directory and its subdirectories.
-    /// Optionally, provide a list of paths to ignore.
-    pub fn extract_paths(path: &str, ignore_paths: Option<Vec<String>>) -> io::Result<Vec<String>> {
-        let exceptions = ignore_paths.unwrap_or_else(|| Vec::new());
-        let mdx_paths_raw = Self::extract_mdx_paths(path).unwrap();
-        let mdx_paths = Self::filter_mdx_paths_for_exceptions(mdx_paths_raw, exceptions);
+    /// Optionally, provide a list of paths to ignore, the extensions of the files
+    /// to extract, e.g. `["md", "mdx"]`, which default to `["mdx"]`, the names
+    /// of the directories to skip, which default to `["contributing"]`, and a list
+    /// of paths to include. Only included paths are kept, before the ignored paths are removed.
+    /// Errors if the path does not exist. A directory without MDX files, or whose
+    /// MDX files are all excluded, is reported and yields no paths.
+    pub fn extract_paths(
+        path: &str,
+        ignore_paths: Option<Vec<String>>,
+        extensions: Option<Vec<String>>,
+        skip_dirs: Option<Vec<String>>,
+        include_paths: Option<Vec<String>>,
+    ) -> io::Result<Vec<String>> {
+        let exceptions = ignore_paths.unwrap_or_default();
+        let inclusions = include_paths.unwrap_or_default();
+        let extensions = extensions.unwrap_or_else(|| vec!["mdx".to_string()]);
+        let skip_dirs = skip_dirs.unwrap_or_else(|| vec!["contributing".to_string()]);
+        if !Path::new(path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Target path does not exist: {}", path),
+            ));
+        }
+        let mdx_paths_raw = Self::extract_mdx_paths(path, &extensions, &skip_dirs)?;
+        if mdx_paths_raw.is_empty() {
+            println!("No MDX files found in {}", path);
+            return Ok(mdx_paths_raw);
+        }
+        let mdx_paths_raw_count = mdx_paths_raw.len();
+        let mdx_paths = Self::filter_mdx_paths_for_inclusions(mdx_paths_raw, inclusions);
+        let mdx_paths = Self::filter_mdx_paths_for_exceptions(mdx_paths, exceptions);
+        if mdx_paths.is_empty() {
+            println!(
+                "All {} MDX files found in {} are excluded by the include or ignore paths",
+                mdx_paths_raw_count, path
+            );
+        }
+
+        Ok(mdx_paths)
+    }
+
+    /// Read MDX paths from a newline-delimited manifest file, keeping the order of the manifest.
+    /// Blank lines and lines starting with `#` are skipped. Include and ignore paths are still applied.
+    /// Returns an error listing every path in the manifest that does not exist.
+    pub fn extract_paths_from_manifest(
+        manifest_path: &str,
+        ignore_paths: Option<Vec<String>>,
+        include_paths: Option<Vec<String>>,
+    ) -> io::Result<Vec<String>> {
+        let exceptions = ignore_paths.unwrap_or_default();
+        let inclusions = include_paths.unwrap_or_default();
+        let manifest = fs::read_to_string(manifest_path)?;
+        let mut mdx_paths = Vec::new();
+        let mut missing_paths = Vec::new();
+
+        for line in manifest.lines() {
+            let path = line.trim();
+            if path.is_empty() || path.starts_with('#') {
+                continue;
+            }
+            if Path::new(path).is_file() {
+                mdx_paths.push(path.to_string());
+            } else {
+                missing_paths.push(path.to_string());
+            }
+        }
+        if !missing_paths.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Files listed in manifest {} not found: {:?}",
+                    manifest_path, missing_paths
+                ),
+            ));
+        }
+        let mdx_paths = Self::filter_mdx_paths_for_inclusions(mdx_paths, inclusions);
+        let mdx_paths = Self::filter_mdx_paths_for_exceptions(mdx_paths, exceptions);
 
         Ok(mdx_paths)
     }
 
+    /// Separate positional arguments from `--flag` arguments.
+    /// Flags listed in `VALUE_FLAGS` consume the argument that follows them,
+    /// while `--backup` takes an optional value inline, e.g. `--backup=.orig`.
+    fn split_args(args: &Vec<String>) -> Result<(Vec<String>, Vec<Flag>), &'static str> {
+        const VALUE_FLAGS: [&str; 12] = [
+            "--files-from",
+            "--generate-master-bib",
+            "--generate-tag-index",
+            "--bibliography-heading",
+            "--bibliography-heading-level",
+            "--bibliography-open",
+            "--bibliography-close",
+            "--output-format",
+            "--extensions",
+            "--skip-dirs",
+            "--include",
+            "--emit-used-bib",
+        ];
+
+        let mut positional = Vec::new();
+        let mut flags = Vec::new();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            if !arg.starts_with("--") {
+                positional.push(arg.clone());
+                continue;
+            }
+            if let Some(suffix) = arg.strip_prefix("--backup=") {
+                flags.push(("--backup".to_string(), Some(suffix.to_string())));
+                continue;
+            }
+            if VALUE_FLAGS.contains(&arg.as_str()) {
+                match iter.next() {
+                    Some(value) => flags.push((arg.clone(), Some(value.clone()))),
+                    None => return Err("Missing value for flag. Usage: --files-from <manifest>, --generate-master-bib <path>, --generate-tag-index <path>, --bibliography-heading <text>, --bibliography-heading-level <1-6>, --bibliography-open <markup>, --bibliography-close <markup>, --output-format <jsx|markdown>, --extensions <md,mdx>, --skip-dirs <dir,dir>, --include <path,path>, --emit-used-bib <path>"),
+                }
+            } else {
+                flags.push((arg.clone(), None));
+            }
+        }
+
+        Ok((positional, flags))
+    }
+
+    /// Apply `--flag` arguments on top of the loaded settings.
+    fn apply_flags(
+        settings: &mut Settings,
+        flags: Vec<Flag>,
+    ) -> Result<(), &'static str> {
+        for (flag, value) in flags {
+            match flag.as_str() {
+                "--files-from" => settings.files_from = value,
+                "--generate-master-bib" => settings.generate_master_bib = value,
+                "--generate-tag-index" => settings.generate_tag_index = value,
+                "--emit-used-bib" => settings.emit_used_bib = value,
+                "--include" => {
+                    settings.include_paths = value.map(|include_paths| {
+                        include_paths
+                            .split(',')
+                            .map(|include_path| include_path.trim().to_string())
+                            .filter(|include_path| !include_path.is_empty())
+                            .collect()
+                    })
+                }
+                "--skip-dirs" => {
+                    settings.skip_dirs = value.map(|skip_dirs| {
+                        skip_dirs
+                            .split(',')
+                            .map(|skip_dir| skip_dir.trim().to_string())
+                            .filter(|skip_dir| !skip_dir.is_empty())
+                            .collect()
+                    })
+                }
+                "--extensions" => {
+                    settings.extensions = value.map(|extensions| {
+                        extensions
+                            .split(',')
+                            .map(|extension| extension.trim().to_string())
+                            .filter(|extension| !extension.is_empty())
+                            .collect()
+                    })
+                }
+                "--bibliography-heading" => settings.bibliography_heading = value,
+                "--bibliography-heading-level" => {
+                    settings.bibliography_heading_level = match value.as_deref().map(str::parse) {
+                        Some(Ok(level)) => Some(level),
+                        _ => return Err("Invalid --bibliography-heading-level. Please provide a level from 1 to 6."),
+                    }
+                }
+                "--bibliography-open" => settings.bibliography_open = value,
+                "--bibliography-close" => settings.bibliography_close = value,
+                "--output-format" => {
+                    settings.output_format = match value.as_deref() {
+                        Some("jsx") => OutputFormat::Jsx,
+                        Some("markdown") => OutputFormat::Markdown,
+                        _ => return Err("Invalid --output-format. Please provide either 'jsx' or 'markdown'."),
+                    }
+                }
+                "--include-drafts" => settings.include_drafts = true,
+                "--lint-bib" => settings.lint_bib = true,
+                "--summary" => settings.summary = true,
+                "--emit-metadata-json" => settings.emit_metadata_json = true,
+                "--bibliography-only" => settings.bibliography_only = true,
+                "--fail-fast" => settings.fail_fast = true,
+                "--warn-unused" => settings.warn_unused = true,
+                "--strict-unused" => settings.strict_unused = true,
+                "--link-citations" => settings.link_citations = true,
+                "--strict-locators" => settings.strict_locators = true,
+                "--dry-run" => settings.dry_run = true,
+                "--backup" => {
+                    if value.as_deref() == Some("") {
+                        return Err("Invalid --backup suffix. Please provide a non-empty suffix, e.g. --backup=.orig.");
+                    }
+                    settings.backup = true;
+                    if value.is_some() {
+                        settings.backup_suffix = value;
+                    }
+                }
+                _ => return Err("Unknown flag. Supported flags: --files-from <manifest>, --generate-master-bib <path>, --generate-tag-index <path>, --bibliography-heading <text>, --bibliography-heading-level <1-6>, --bibliography-open <markup>, --bibliography-close <markup>, --output-format <jsx|markdown>, --extensions <md,mdx>, --skip-dirs <dir,dir>, --include <path,path>, --emit-used-bib <path>, --include-drafts, --lint-bib, --summary, --emit-metadata-json, --bibliography-only, --fail-fast, --warn-unused, --strict-unused, --link-citations, --strict-locators, --dry-run, --backup[=suffix]"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that a citation regex override compiles and has a capture group for the citation.
+    fn validate_citation_regex(pattern: &str) -> Result<(), &'static str> {
+        match Regex::new(pattern) {
+            Ok(citation_regex) if citation_regex.captures_len() > 1 => Ok(()),
+            Ok(_) => Err("Invalid citation_regex_override: the pattern needs a capture group for the citation."),
+            Err(_) => Err("Invalid citation_regex_override: the pattern is not a valid regular expression."),
+        }
+    }
+
     /// Build configuration from arguments to be used internally.
     pub fn build_config(
         args: &Vec<String>,
@@ -179,31 +694,55 @@ impl Utils {
             println!("  args[{}]: {}", i, arg);
         }
 
-        
+        let (args, flags) = Self::split_args(args)?;
+
         if args.len() < 4 {
             return Err("Arguments missing: <bibliography.bib> <target_dir_or_file> <mode>");
         }
-        if !args[1].ends_with(".bib") {
+        if !Self::split_bib_files(&args[1])
+            .iter()
+            .all(|bib_file| bib_file.ends_with(".bib"))
+        {
             return Err("Invalid file format. Please provide a file with .bib extension.");
         }
-        let target_arg = &args[2];
-        if !Path::new(target_arg).is_dir() && !target_arg.ends_with(".mdx") {
-            return Err("Invalid target. Please provide a directory or a single MDX file.");
-        }
-        if !args[3].eq("verify") && !args[3].eq("process") {
-            return Err("Invalid mode. Please provide either 'verify' or 'process'.");
+        if !args[3].eq("verify") && !args[3].eq("process") && !args[3].eq("clean") {
+            return Err("Invalid mode. Please provide either 'verify', 'process' or 'clean'.");
         }
 
-        let settings: Settings;
+        let mut settings: Settings;
         if args.len() == 5 {
-            let ignore_parts_vector: Vec<String> =
-                args[4].split(',').map(|s| s.to_string()).collect();
+            let ignore_parts_vector: Vec<String> = args[4]
+                .split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect();
             settings = Settings {
                 ignore_paths: ignore_parts_vector,
+                ..Default::default()
             };
         } else {
             settings = Self::load_or_create_settings("prepyrus_settings.json", test_mode).unwrap();
         }
+        Self::apply_flags(&mut settings, flags)?;
+        // Single-file targets are checked against the extensions, which may be set by the settings or a flag
+        let target_arg = &args[2];
+        let extensions = settings.extensions.clone().unwrap_or_else(|| vec!["mdx".to_string()]);
+        if !Path::new(target_arg).is_dir() && !Self::has_extension(target_arg, &extensions) {
+            return Err("Invalid target. Please provide a directory or a single file with one of the accepted extensions (.mdx by default).");
+        }
+        if let Some(pattern) = &settings.citation_regex_override {
+            Self::validate_citation_regex(pattern)?;
+        }
+        Self::validate_path_patterns(&settings.ignore_paths)?;
+        if let Some(include_paths) = &settings.include_paths {
+            Self::validate_path_patterns(include_paths)?;
+        }
+        if let Some(level) = settings.bibliography_heading_level {
+            if !(1..=6).contains(&level) {
+                return Err("Invalid bibliography_heading_level. Please provide a level from 1 to 6.");
+            }
+        }
 
         let config = Config {
             bib_file: args[1].clone(),
@@ -215,13 +754,28 @@ impl Utils {
         Ok(config)
     }
 
+    /// Split the bibliography argument into its files, separated with commas if multiple.
+    pub fn split_bib_files(bib_file: &str) -> Vec<String> {
+        bib_file
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect()
+    }
+
     /// Excavates all MDX files in a directory and its subdirectories
     /// and returns a vector of paths to the MDX files.
-    /// The function skips the "contributing" folder.
-    fn extract_mdx_paths(path: &str) -> io::Result<Vec<String>> {
+    /// Files are matched by their extension against the given extensions.
+    /// Subdirectories named as one of the skipped directories, e.g. "contributing", are not walked.
+    fn extract_mdx_paths(
+        path: &str,
+        extensions: &Vec<String>,
+        skip_dirs: &Vec<String>,
+    ) -> io::Result<Vec<String>> {
         let mut mdx_paths = Vec::new();
 
-        if !Path::new(path).is_dir() && path.ends_with(".mdx") {
+        if !Path::new(path).is_dir() && Self::has_extension(path, extensions) {
             mdx_paths.push(path.to_string());
             return Ok(mdx_paths);
         }
@@ -233,33 +787,107 @@ impl Utils {
             let path = entry.path();
 
             if path.is_dir() {
-                let sub_paths = Self::extract_mdx_paths(path.to_str().unwrap())?;
+                let is_skipped = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| skip_dirs.iter().any(|skip_dir| skip_dir.trim() == name));
+                if is_skipped {
+                    continue;
+                }
+                let sub_paths =
+                    Self::extract_mdx_paths(path.to_str().unwrap(), extensions, skip_dirs)?;
                 mdx_paths.extend(sub_paths);
-            } else if path.is_file() && path.extension() == Some(std::ffi::OsStr::new("mdx")) {
+            } else if path.is_file() && Self::has_extension(path.to_str().unwrap(), extensions) {
                 mdx_paths.push(path.to_str().unwrap().to_string());
             }
         }
-        if mdx_paths.is_empty() {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                "No MDX files found in the directory",
-            ));
-        }
         Ok(mdx_paths)
     }
 
+    /// Check whether the path has one of the extensions, given with or without a leading dot.
+    /// Extensions are compared case-insensitively.
+    fn has_extension(path: &str, extensions: &Vec<String>) -> bool {
+        match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+            Some(extension) => extensions
+                .iter()
+                .any(|accepted| accepted.trim().trim_start_matches('.').eq_ignore_ascii_case(extension)),
+            None => false,
+        }
+    }
+
     /// Filter MDX paths for exceptions.
+    /// Exceptions with glob characters, e.g. `tests/mocks/data/drafts/*.mdx` or `**/archive/**`,
+    /// are matched against the whole path. Other exceptions match any path containing them.
     fn filter_mdx_paths_for_exceptions(
         mdx_paths: Vec<String>,
         exceptions: Vec<String>,
     ) -> Vec<String> {
         let mut filtered_paths = mdx_paths.clone();
+        let exceptions = Self::compile_path_patterns(&exceptions);
         if exceptions.is_empty() {
             return filtered_paths;
         }
-        filtered_paths.retain(|path| !exceptions.iter().any(|exception| path.contains(exception)));
+        filtered_paths.retain(|path| !Self::matches_path_patterns(path, &exceptions));
         filtered_paths
     }
+
+    /// Keep only the MDX paths matching any of the inclusions, which are matched like the exceptions.
+    /// All paths are kept when there are no inclusions.
+    fn filter_mdx_paths_for_inclusions(
+        mdx_paths: Vec<String>,
+        inclusions: Vec<String>,
+    ) -> Vec<String> {
+        let mut filtered_paths = mdx_paths.clone();
+        let inclusions = Self::compile_path_patterns(&inclusions);
+        if inclusions.is_empty() {
+            return filtered_paths;
+        }
+        filtered_paths.retain(|path| Self::matches_path_patterns(path, &inclusions));
+        filtered_paths
+    }
+
+    /// Whether the pattern is a glob rather than a plain substring of the path.
+    fn is_glob_pattern(pattern: &str) -> bool {
+        pattern.contains(['*', '?', '[', '{'])
+    }
+
+    /// Compile path patterns into substrings and glob matchers. `*` does not cross directories
+    /// but `**` does. Blank patterns, which would match every path, are left out,
+    /// and invalid globs are kept as plain substrings.
+    fn compile_path_patterns(patterns: &Vec<String>) -> Vec<(String, Option<GlobMatcher>)> {
+        patterns
+            .iter()
+            .map(|pattern| pattern.trim())
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| {
+                let matcher = Self::is_glob_pattern(pattern)
+                    .then(|| GlobBuilder::new(pattern).literal_separator(true).build().ok())
+                    .flatten()
+                    .map(|glob| glob.compile_matcher());
+                (pattern.to_string(), matcher)
+            })
+            .collect()
+    }
+
+    /// Check whether the path matches any of the compiled patterns, ignoring a leading `./`.
+    fn matches_path_patterns(path: &str, patterns: &Vec<(String, Option<GlobMatcher>)>) -> bool {
+        let path = path.strip_prefix("./").unwrap_or(path);
+        patterns.iter().any(|(pattern, matcher)| match matcher {
+            Some(matcher) => matcher.is_match(path),
+            None => path.contains(pattern.as_str()),
+        })
+    }
+
+    /// Check that the glob patterns among the include or ignore paths are valid.
+    fn validate_path_patterns(patterns: &Vec<String>) -> Result<(), &'static str> {
+        let is_invalid = |pattern: &&String| {
+            Self::is_glob_pattern(pattern) && GlobBuilder::new(pattern.trim()).build().is_err()
+        };
+        match patterns.iter().find(is_invalid) {
+            Some(_) => Err("Invalid include or ignore path: the glob pattern could not be parsed."),
+            None => Ok(()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -306,6 +934,7 @@ mod tests_utils {
                 "tests/mocks/data/engels.mdx".to_string(),
                 "tests/mocks/data/marx.mdx".to_string(),
             ],
+            ..Default::default()
         };
         let config_json = serde_json::to_string_pretty(&modified_settings)
             .expect("Failed to serialize modified settings");
@@ -324,4 +953,565 @@ mod tests_utils {
         fs::remove_file(test_settings_path).expect("Failed to remove the test settings file");
         assert!(!std::path::Path::new(test_settings_path).exists());
     }
+
+    #[test]
+    fn extract_paths_from_manifest_keeps_order_and_ignores() {
+        let manifest_path = "test_prepyrus_manifest.txt";
+        fs::write(
+            manifest_path,
+            "tests/mocks/data/science-of-logic-introduction.mdx\n\
+            # comment line\n\
+            tests/mocks/data/development.mdx\n\
+            \n\
+            tests/mocks/data/first-paragraph.mdx\n",
+        )
+        .expect("Failed to write manifest");
+
+        let mdx_paths = Utils::extract_paths_from_manifest(
+            manifest_path,
+            Some(vec!["tests/mocks/data/development.mdx".to_string()]),
+            None,
+        );
+        fs::remove_file(manifest_path).expect("Failed to remove manifest");
+
+        assert_eq!(
+            mdx_paths.expect("Failed to read manifest"),
+            vec![
+                "tests/mocks/data/science-of-logic-introduction.mdx",
+                "tests/mocks/data/first-paragraph.mdx"
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_paths_from_manifest_reports_missing_files() {
+        let manifest_path = "test_prepyrus_manifest_missing.txt";
+        fs::write(
+            manifest_path,
+            "tests/mocks/data/development.mdx\ntests/mocks/data/does-not-exist.mdx\n",
+        )
+        .expect("Failed to write manifest");
+
+        let result = Utils::extract_paths_from_manifest(manifest_path, None, None);
+        fs::remove_file(manifest_path).expect("Failed to remove manifest");
+
+        let err = result.expect_err("Missing manifest entries should be reported");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("tests/mocks/data/does-not-exist.mdx"));
+    }
+
+    #[test]
+    fn build_config_with_files_from_flag() {
+        let args = vec![
+            "program_index".to_string(),
+            "tests/mocks/test.bib".to_string(),
+            "tests/mocks/data".to_string(),
+            "--files-from".to_string(),
+            "manifest.txt".to_string(),
+            "verify".to_string(),
+        ];
+        let config = Utils::build_config(&args, Some(LoadOrCreateSettingsTestMode::Test))
+            .expect("Failed to build config");
+
+        assert_eq!(config.mode, "verify");
+        assert_eq!(config.settings.files_from, Some("manifest.txt".to_string()));
+    }
+
+    #[test]
+    fn build_config_with_bibliography_heading_flags() {
+        let args = |level: &str| {
+            vec![
+                "program_index".to_string(),
+                "tests/mocks/test.bib".to_string(),
+                "tests/mocks/data".to_string(),
+                "process".to_string(),
+                "--bibliography-heading".to_string(),
+                "References".to_string(),
+                "--bibliography-heading-level".to_string(),
+                level.to_string(),
+                "--bibliography-open".to_string(),
+                "<section>".to_string(),
+            ]
+        };
+        let config = Utils::build_config(&args("3"), Some(LoadOrCreateSettingsTestMode::Test))
+            .expect("Failed to build config");
+
+        assert_eq!(config.settings.bibliography_heading, Some("References".to_string()));
+        assert_eq!(config.settings.bibliography_heading_level, Some(3));
+        assert_eq!(config.settings.bibliography_open, Some("<section>".to_string()));
+        assert!(Utils::build_config(&args("7"), Some(LoadOrCreateSettingsTestMode::Test)).is_err());
+        assert!(Utils::build_config(&args("two"), Some(LoadOrCreateSettingsTestMode::Test)).is_err());
+    }
+
+    #[test]
+    fn build_config_with_output_format_flag() {
+        let args = |format: &str| {
+            vec![
+                "program_index".to_string(),
+                "tests/mocks/test.bib".to_string(),
+                "tests/mocks/data".to_string(),
+                "process".to_string(),
+                "--output-format".to_string(),
+                format.to_string(),
+            ]
+        };
+        let config = Utils::build_config(&args("markdown"), Some(LoadOrCreateSettingsTestMode::Test))
+            .expect("Failed to build config");
+
+        assert_eq!(config.settings.output_format, OutputFormat::Markdown);
+        assert!(Utils::build_config(&args("html"), Some(LoadOrCreateSettingsTestMode::Test)).is_err());
+    }
+
+    #[test]
+    fn build_config_with_backup_flag() {
+        let args = |flag: &str| {
+            vec![
+                "program_index".to_string(),
+                "tests/mocks/test.bib".to_string(),
+                "tests/mocks/data".to_string(),
+                "process".to_string(),
+                flag.to_string(),
+            ]
+        };
+        let config = Utils::build_config(&args("--backup"), Some(LoadOrCreateSettingsTestMode::Test))
+            .expect("Failed to build config");
+        assert!(config.settings.backup);
+        assert_eq!(config.settings.backup_suffix, None);
+
+        let config = Utils::build_config(&args("--backup=.orig"), Some(LoadOrCreateSettingsTestMode::Test))
+            .expect("Failed to build config");
+        assert!(config.settings.backup);
+        assert_eq!(config.settings.backup_suffix, Some(".orig".to_string()));
+        assert_eq!(config.mode, "process");
+
+        assert!(Utils::build_config(&args("--backup="), Some(LoadOrCreateSettingsTestMode::Test)).is_err());
+    }
+
+    #[test]
+    fn output_format_in_settings_file() {
+        let settings: Settings = serde_json::from_str(r#"{"output_format": "markdown"}"#).unwrap();
+        assert_eq!(settings.output_format, OutputFormat::Markdown);
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.output_format, OutputFormat::Jsx);
+    }
+
+    #[test]
+    fn check_entries_consistency_flags_mistyped_entries() {
+        let bibliography = Bibliography::parse(
+            "@article{mistyped, title = {A Book}, author = {Doe, Jane}, publisher = {Press}, year = {2020}}
+            @article{nojournal, title = {An Article}, author = {Doe, Jane}, year = {2020}}
+            @book{nopublisher, title = {A Book}, author = {Doe, Jane}, year = {2020}}
+            @book{nodate, title = {A Book}, author = {Doe, Jane}, publisher = {Press}}
+            @article{fine, title = {An Article}, author = {Doe, Jane}, journal = {Journal}, year = {2020}}",
+        )
+        .expect("Failed to parse bibliography");
+
+        let warnings = BiblatexUtils::check_entries_consistency(&bibliography.into_vec());
+
+        assert_eq!(warnings.len(), 4);
+        assert!(warnings[0].starts_with("mistyped: @article has a publisher but no journal"));
+        assert!(warnings[1].starts_with("nojournal: @article has no journal"));
+        assert!(warnings[2].starts_with("nopublisher: @book has no publisher"));
+        assert!(warnings[3].starts_with("nodate: entry has no date or year"));
+    }
+
+    #[test]
+    fn retrieve_merged_bibliography_entries() {
+        let bib_files = Utils::split_bib_files("tests/mocks/test.bib, tests/mocks/test-topics.bib");
+        let entries = BiblatexUtils::retrieve_merged_bibliography_entries(&bib_files)
+            .expect("Failed to merge bibliography entries");
+        assert!(entries.iter().any(|entry| entry.key == "hegel2010logic"));
+        assert!(entries.iter().any(|entry| entry.key == "pippin1989idealism"));
+    }
+
+    #[test]
+    fn retrieve_merged_bibliography_entries_with_duplicate_keys() {
+        let bib_files = vec![
+            "tests/mocks/test-topics.bib".to_string(),
+            "tests/mocks/test-topics.bib".to_string(),
+        ];
+        match BiblatexUtils::retrieve_merged_bibliography_entries(&bib_files) {
+            Err(BibliographyError::DuplicateKeys(keys)) => {
+                assert_eq!(keys, vec!["pippin1989idealism", "pinkard1994phenomenology"])
+            }
+            _ => panic!("Expected duplicate keys"),
+        }
+    }
+
+    #[test]
+    fn retrieve_bibliography_entries_with_duplicate_keys() {
+        match BiblatexUtils::retrieve_bibliography_entries("tests/mocks/duplicate-keys.bib") {
+            Err(BibliographyError::DuplicateKeys(keys)) => assert_eq!(keys, vec!["hegel2010logic"]),
+            _ => panic!("Expected duplicate keys"),
+        }
+        let bib_files = vec!["tests/mocks/duplicate-keys.bib".to_string()];
+        assert!(matches!(
+            BiblatexUtils::retrieve_merged_bibliography_entries(&bib_files),
+            Err(BibliographyError::DuplicateKeys(_))
+        ));
+    }
+
+    #[test]
+    fn extract_spanned_chunk_keeps_math_and_verbatim_chunks() {
+        let bibliography = Bibliography::parse(
+            "@book{doe2020phi,
+                title = {The $\\phi$ of {Spirit}},
+                author = {Doe, Jane},
+                year = {2020}
+            }",
+        )
+        .expect("Failed to parse bibliography");
+        let entry = &bibliography.into_vec()[0];
+        let title = BiblatexUtils::extract_spanned_chunk(entry.get("title").unwrap());
+        assert_eq!(title, "The $\\phi$ of Spirit");
+    }
+
+    #[test]
+    fn extract_pages_single_page() {
+        assert_eq!(BiblatexUtils::extract_pages(&PermissiveType::Typed(vec![42..42])), "42");
+    }
+
+    #[test]
+    fn extract_pages_single_range() {
+        assert_eq!(BiblatexUtils::extract_pages(&PermissiveType::Typed(vec![12..34])), "12–34");
+        assert_eq!(BiblatexUtils::extract_pages(&PermissiveType::Typed(vec![181..184])), "181–84");
+    }
+
+    #[test]
+    fn extract_pages_multiple_ranges() {
+        let pages = PermissiveType::Typed(vec![12..34, 45..67, 99..99]);
+        assert_eq!(BiblatexUtils::extract_pages(&pages), "12–34, 45–67, 99");
+    }
+
+    #[test]
+    fn format_page_ranges_per_chicago_style() {
+        let cases = [
+            ((3, 10), "3–10"),
+            ((71, 72), "71–72"),
+            ((96, 117), "96–117"),
+            ((100, 104), "100–104"),
+            ((1100, 1113), "1100–1113"),
+            ((101, 108), "101–8"),
+            ((808, 833), "808–33"),
+            ((1103, 1104), "1103–4"),
+            ((321, 328), "321–28"),
+            ((498, 532), "498–532"),
+            ((1087, 1089), "1087–89"),
+            ((1496, 1500), "1496–500"),
+            ((11564, 11615), "11564–615"),
+            ((12991, 13001), "12991–3001"),
+        ];
+        for ((start, end), expected) in cases {
+            assert_eq!(BiblatexUtils::format_page_range(start, end), expected);
+        }
+    }
+
+    #[test]
+    fn check_entries_consistency_on_mock_bibliography() {
+        let entries = BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries");
+        assert!(BiblatexUtils::check_entries_consistency(&entries).is_empty());
+    }
+
+    #[test]
+    fn extract_paths_nonexistent_target() {
+        let err = Utils::extract_paths("tests/mocks/does-not-exist", None, None, None, None)
+            .expect_err("A nonexistent target should be an error");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("does not exist"));
+    }
+
+    #[test]
+    fn extract_paths_without_mdx_files() {
+        let empty_section = "tests/mocks/empty_section_test";
+        create_dir_all(format!("{}/nested", empty_section)).expect("Failed to create directory");
+        fs::write(format!("{}/notes.txt", empty_section), "No MDX here")
+            .expect("Failed to write file");
+
+        let mdx_paths = Utils::extract_paths(empty_section, None, None, None, None);
+        fs::remove_dir_all(empty_section).expect("Failed to remove directory");
+
+        assert!(mdx_paths.expect("An empty section is not an error").is_empty());
+    }
+
+    #[test]
+    fn extract_paths_all_ignored() {
+        let mdx_paths = Utils::extract_paths(
+            "tests/mocks/data",
+            Some(vec!["tests/mocks/data".to_string()]),
+            None,
+            None,
+            None,
+        )
+        .expect("Ignoring every file is not an error");
+        assert!(mdx_paths.is_empty());
+    }
+
+    #[test]
+    fn extract_paths_walks_past_directories_without_mdx_files() {
+        let section = "tests/mocks/mixed_section_test";
+        create_dir_all(format!("{}/images", section)).expect("Failed to create directory");
+        fs::write(format!("{}/article.mdx", section), "---\n---\n")
+            .expect("Failed to write file");
+
+        let mdx_paths = Utils::extract_paths(section, None, None, None, None);
+        fs::remove_dir_all(section).expect("Failed to remove directory");
+
+        assert_eq!(
+            mdx_paths.expect("Failed to extract paths"),
+            vec![format!("{}/article.mdx", section)]
+        );
+    }
+
+    #[test]
+    fn extract_paths_with_extensions() {
+        let section = "tests/mocks/extensions_section_test";
+        create_dir_all(section).expect("Failed to create directory");
+        for file in ["article.md", "article.mdx", "notes.txt"] {
+            fs::write(format!("{}/{}", section, file), "---\n---\n").expect("Failed to write file");
+        }
+
+        let md_paths = Utils::extract_paths(section, None, Some(vec!["md".to_string()]), None, None);
+        let all_paths =
+            Utils::extract_paths(
+            section,
+            None,
+            Some(vec![".MD".to_string(), "mdx".to_string()]),
+            None,
+            None,
+        );
+        fs::remove_dir_all(section).expect("Failed to remove directory");
+
+        assert_eq!(
+            md_paths.expect("Failed to extract paths"),
+            vec![format!("{}/article.md", section)]
+        );
+        let mut all_paths = all_paths.expect("Failed to extract paths");
+        all_paths.sort();
+        assert_eq!(
+            all_paths,
+            vec![format!("{}/article.md", section), format!("{}/article.mdx", section)]
+        );
+    }
+
+    #[test]
+    fn extract_paths_skips_contributing_and_configured_dirs() {
+        let section = "tests/mocks/skip_dirs_section_test";
+        for dir in ["contributing", "drafts", "logic"] {
+            create_dir_all(format!("{}/{}", section, dir)).expect("Failed to create directory");
+            fs::write(format!("{}/{}/article.mdx", section, dir), "---\n---\n")
+                .expect("Failed to write file");
+        }
+
+        let default_paths = Utils::extract_paths(section, None, None, None, None);
+        let configured_paths =
+            Utils::extract_paths(section, None, None, Some(vec!["drafts".to_string()]), None);
+        fs::remove_dir_all(section).expect("Failed to remove directory");
+
+        let mut default_paths = default_paths.expect("Failed to extract paths");
+        default_paths.sort();
+        assert_eq!(
+            default_paths,
+            vec![
+                format!("{}/drafts/article.mdx", section),
+                format!("{}/logic/article.mdx", section)
+            ]
+        );
+        let mut configured_paths = configured_paths.expect("Failed to extract paths");
+        configured_paths.sort();
+        assert_eq!(
+            configured_paths,
+            vec![
+                format!("{}/contributing/article.mdx", section),
+                format!("{}/logic/article.mdx", section)
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_paths_with_include_and_ignore_paths() {
+        let section = "tests/mocks/include_paths_section_test";
+        for file in ["logic/being.mdx", "logic/nothing.mdx", "history/rome.mdx"] {
+            let path = format!("{}/{}", section, file);
+            create_dir_all(Path::new(&path).parent().unwrap()).expect("Failed to create directory");
+            fs::write(path, "---\n---\n").expect("Failed to write file");
+        }
+        let extract = |ignore_paths: Option<Vec<&str>>, include_paths: Option<Vec<&str>>| {
+            let to_strings =
+                |paths: Vec<&str>| paths.iter().map(|path| path.to_string()).collect::<Vec<String>>();
+            let mut mdx_paths = Utils::extract_paths(
+                section,
+                ignore_paths.map(to_strings),
+                None,
+                None,
+                include_paths.map(to_strings),
+            )
+            .expect("Failed to extract paths");
+            mdx_paths.sort();
+            mdx_paths
+        };
+
+        let include_only = extract(None, Some(vec!["**/logic/**"]));
+        let ignore_only = extract(Some(vec!["**/logic/**"]), None);
+        let both = extract(Some(vec!["nothing.mdx"]), Some(vec!["logic/"]));
+        fs::remove_dir_all(section).expect("Failed to remove directory");
+
+        assert_eq!(
+            include_only,
+            vec![
+                format!("{}/logic/being.mdx", section),
+                format!("{}/logic/nothing.mdx", section)
+            ]
+        );
+        assert_eq!(ignore_only, vec![format!("{}/history/rome.mdx", section)]);
+        // Ignore paths are applied after the include paths
+        assert_eq!(both, vec![format!("{}/logic/being.mdx", section)]);
+    }
+
+    #[test]
+    fn build_config_with_include_flag() {
+        let args = vec![
+            "program_index".to_string(),
+            "tests/mocks/test.bib".to_string(),
+            "tests/mocks/data".to_string(),
+            "verify".to_string(),
+            "--include".to_string(),
+            "**/logic/**, drafts/".to_string(),
+            "--include-drafts".to_string(),
+        ];
+        let config = Utils::build_config(&args, Some(LoadOrCreateSettingsTestMode::Test))
+            .expect("Failed to build config");
+
+        assert_eq!(
+            config.settings.include_paths,
+            Some(vec!["**/logic/**".to_string(), "drafts/".to_string()])
+        );
+        assert!(config.settings.include_drafts);
+    }
+
+    #[test]
+    fn build_config_with_md_target() {
+        let args = |flags: &[&str]| {
+            let mut args = vec![
+                "program_index".to_string(),
+                "tests/mocks/test.bib".to_string(),
+                "tests/mocks/data/plain-markdown.md".to_string(),
+                "verify".to_string(),
+            ];
+            args.extend(flags.iter().map(|flag| flag.to_string()));
+            args
+        };
+        assert!(Utils::build_config(&args(&[]), Some(LoadOrCreateSettingsTestMode::Test)).is_err());
+
+        let config = Utils::build_config(
+            &args(&["--extensions", "md,mdx"]),
+            Some(LoadOrCreateSettingsTestMode::Test),
+        )
+        .expect("Failed to build config");
+        assert_eq!(
+            config.settings.extensions,
+            Some(vec!["md".to_string(), "mdx".to_string()])
+        );
+    }
+
+    #[test]
+    fn build_config_trims_ignore_paths() {
+        let args = vec![
+            "program_index".to_string(),
+            "tests/mocks/test.bib".to_string(),
+            "tests/mocks/data".to_string(),
+            "verify".to_string(),
+            "tests/mocks/data/development.mdx, tests/mocks/data/first-paragraph.mdx,".to_string(),
+        ];
+        let config = Utils::build_config(&args, None).expect("Failed to build config");
+
+        assert_eq!(
+            config.settings.ignore_paths,
+            vec![
+                "tests/mocks/data/development.mdx",
+                "tests/mocks/data/first-paragraph.mdx"
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_mdx_paths_skips_blank_exceptions() {
+        let mdx_paths = vec!["a.mdx".to_string(), "b.mdx".to_string()];
+        let filtered = Utils::filter_mdx_paths_for_exceptions(
+            mdx_paths,
+            vec![" b.mdx".to_string(), "".to_string()],
+        );
+        assert_eq!(filtered, vec!["a.mdx"]);
+    }
+
+    #[test]
+    fn filter_mdx_paths_with_glob_patterns() {
+        let mdx_paths = vec![
+            "tests/mocks/data/intro.mdx".to_string(),
+            "tests/mocks/data/drafts/draft.mdx".to_string(),
+            "tests/mocks/data/drafts/nested/draft.mdx".to_string(),
+            "tests/mocks/data/archive/old.mdx".to_string(),
+            "./logic/archive/older.mdx".to_string(),
+        ];
+        let filter = |exception: &str| {
+            Utils::filter_mdx_paths_for_exceptions(mdx_paths.clone(), vec![exception.to_string()])
+        };
+
+        // `*` does not cross directories
+        assert_eq!(
+            filter("tests/mocks/data/drafts/*.mdx"),
+            vec![
+                "tests/mocks/data/intro.mdx",
+                "tests/mocks/data/drafts/nested/draft.mdx",
+                "tests/mocks/data/archive/old.mdx",
+                "./logic/archive/older.mdx"
+            ]
+        );
+        // `**` does, and a leading `./` is ignored
+        assert_eq!(
+            filter("**/archive/**"),
+            vec![
+                "tests/mocks/data/intro.mdx",
+                "tests/mocks/data/drafts/draft.mdx",
+                "tests/mocks/data/drafts/nested/draft.mdx"
+            ]
+        );
+        assert_eq!(
+            filter("tests/mocks/data/drafts/**"),
+            vec![
+                "tests/mocks/data/intro.mdx",
+                "tests/mocks/data/archive/old.mdx",
+                "./logic/archive/older.mdx"
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_mdx_paths_with_exact_paths_and_substrings() {
+        let mdx_paths = vec![
+            "tests/mocks/data/intro.mdx".to_string(),
+            "tests/mocks/data/drafts/draft.mdx".to_string(),
+        ];
+        let filter = |exception: &str| {
+            Utils::filter_mdx_paths_for_exceptions(mdx_paths.clone(), vec![exception.to_string()])
+        };
+
+        assert_eq!(filter("tests/mocks/data/intro.mdx"), vec!["tests/mocks/data/drafts/draft.mdx"]);
+        // Plain exceptions still match as substrings
+        assert_eq!(filter("drafts/"), vec!["tests/mocks/data/intro.mdx"]);
+    }
+
+    #[test]
+    fn validate_ignore_path_patterns() {
+        assert!(Utils::validate_path_patterns(&vec!["**/archive/**".to_string()]).is_ok());
+        assert!(Utils::validate_path_patterns(&vec!["drafts/".to_string()]).is_ok());
+        assert!(Utils::validate_path_patterns(&vec!["drafts/[a-".to_string()]).is_err());
+    }
+
+    #[test]
+    fn validate_citation_regex_override() {
+        assert!(Utils::validate_citation_regex(r"\[(?P<citation>[A-Z][^\]]*)\]").is_ok());
+        assert!(Utils::validate_citation_regex(r"\[[A-Z][^\]]*\]").is_err());
+        assert!(Utils::validate_citation_regex(r"\[(unclosed").is_err());
+    }
 }
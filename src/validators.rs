@@ -1,19 +1,89 @@
+use crate::errors::{CitationError, CitationErrors};
+use crate::{inserters, transformers};
+use crate::transformers::DEFAULT_NO_DATE_TOKEN;
+use crate::utils::Settings;
 use crate::BiblatexUtils;
-use biblatex::Entry;
+use biblatex::{Entry, RetrievalError};
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs;
 use std::io::{self, BufReader, Error, Read};
+use std::sync::OnceLock;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Metadata {
+    // Required fields are checked by name before deserializing, see `DEFAULT_REQUIRED_METADATA`
+    #[serde(default)]
     pub title: String,
+    #[serde(default)]
     pub description: String,
-    #[serde(rename = "isArticle")]
+    #[serde(default, rename = "isArticle")]
     pub is_article: bool,
+    /// Authors as a pre-formatted string, or as a list of names or of objects with a `name`
+    /// and an optional `url`, which is rendered with one person per line.
+    #[serde(default, deserialize_with = "deserialize_people")]
     pub authors: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_people")]
     pub editors: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_people")]
     pub contributors: Option<String>,
+    /// Title of the article as listed in indexes, if it differs from the title.
+    #[serde(rename = "indexTitle")]
+    pub index_title: Option<String>,
+    /// Tags of the article, e.g. `tags: [logic, metaphysics]`. Also read from `keywords`.
+    #[serde(default, alias = "keywords")]
+    pub tags: Vec<String>,
+    /// Work-in-progress articles are skipped unless drafts are explicitly included.
+    #[serde(default)]
+    pub draft: bool,
+    /// Any other keys of the frontmatter, e.g. `slug` or `lastUpdated`, kept as they were read.
+    #[serde(flatten)]
+    pub extra: serde_yaml::Mapping,
+}
+
+/// People in the frontmatter, either pre-formatted or listed one by one.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum People {
+    Formatted(String),
+    Listed(Vec<Person>),
+}
+
+/// A listed person, either by name alone or by name with a link.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Person {
+    Name(String),
+    Linked { name: String, url: Option<String> },
+}
+
+/// Deserializes the people of the frontmatter into the block rendered under their heading,
+/// e.g. `[Jane Doe, John Roe]` into "Jane Doe  \nJohn Roe", with a link for people with a `url`.
+fn deserialize_people<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let people = match Option::<People>::deserialize(deserializer)? {
+        Some(people) => people,
+        None => return Ok(None),
+    };
+    let rendered = match people {
+        People::Formatted(people) => people,
+        People::Listed(people) => people
+            .into_iter()
+            .map(|person| match person {
+                Person::Name(name) | Person::Linked { name, url: None } => name,
+                Person::Linked {
+                    name,
+                    url: Some(url),
+                } => format!("[{}]({})", name, url),
+            })
+            .collect::<Vec<_>>()
+            .join("  \n"),
+    };
+    Ok(Some(rendered))
 }
 
 #[derive(Debug)]
@@ -25,245 +95,950 @@ pub struct ArticleFileData {
     pub full_file_content: String,
 }
 
+/// Report of `verify`, with the verified articles and the totals of what was verified.
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub articles: Vec<ArticleFileData>,
+    /// Number of files scanned, including skipped ones.
+    pub total_files: usize,
+    /// Number of articles verified.
+    pub total_articles: usize,
+    /// Number of files skipped as they are not articles, or are drafts.
+    pub total_skipped: usize,
+    /// Number of citations matched to the bibliography across all articles, including repeated citations.
+    pub total_citations_matched: usize,
+}
+
+impl std::fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} files verified, including {} articles with {} matched citations. {} files were skipped",
+            self.total_files, self.total_articles, self.total_citations_matched, self.total_skipped
+        )
+    }
+}
+
+/// A citation extracted from the markdown, e.g. "Hegel 2010, 61",
+/// with the 1-based line it appears on.
+#[derive(Debug, Clone, PartialEq)]
+struct Citation {
+    text: String,
+    line: usize,
+}
+
+impl PartialEq<&str> for Citation {
+    fn eq(&self, other: &&str) -> bool {
+        self.text == *other
+    }
+}
+
+/// Citations with the given texts, each on the line of its position, e.g. for tests.
+#[cfg(test)]
+fn citations_from(texts: &[&str]) -> Vec<Citation> {
+    texts
+        .iter()
+        .enumerate()
+        .map(|(index, text)| Citation {
+            text: text.to_string(),
+            line: index + 1,
+        })
+        .collect()
+}
+
 /// Verifies the integrity of MDX files.
 /// The function reads the MDX files, extracts metadata and markdown content,
 /// verifies the citations format, and matches the citations to the bibliography.
 /// The function returns a list of ArticleFileData structs containing the metadata,
 /// markdown content, matched citations, and full file content.
+/// Optional lints are enabled through the settings.
+/// Verification never writes to the filesystem, so it can serve as a read-only gate.
+/// Unreadable files and malformed or unmatched citations are returned as errors
+/// for the caller to handle. The citation errors of all files are collected and returned together
+/// as `CitationErrors`, unless `fail_fast` is set, in which case the first file with errors fails.
+/// Returns the verified articles along with the totals of files, articles and citations.
 pub fn verify_mdx_files(
     mdx_paths: Vec<String>,
     all_entries: &Vec<Entry>,
-) -> Result<Vec<ArticleFileData>, Error> {
-    let mut article_count = 0;
-    let mut all_articles: Vec<ArticleFileData> = Vec::new();
-    for mdx_path in &mdx_paths {
-        let (metadata, markdown_content, full_file_content) = match read_mdx_file(&mdx_path) {
-            Ok(data) => data,
-            Err(err) => {
-                if err.kind() == io::ErrorKind::InvalidData {
-                    eprintln!("Invalid MDX data format: {}", err);
-                    std::process::exit(1);
-                } else {
-                    eprintln!("Unexpected error reading MDX file: {}", err);
-                    std::process::exit(1);
-                }
+    settings: &Settings,
+) -> Result<VerifyReport, Error> {
+    let mut verify_report = VerifyReport {
+        total_files: mdx_paths.len(),
+        ..Default::default()
+    };
+    let mut citation_errors: Vec<(String, usize, CitationError)> = Vec::new();
+    let citation_regex_override = match &settings.citation_regex_override {
+        Some(pattern) => Some(Regex::new(pattern).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid citation regex override: {}", err),
+            )
+        })?),
+        None => None,
+    };
+    let configured_citation_regexes = citation_regexes_for(settings);
+    let citation_regexes: Vec<&Regex> = configured_citation_regexes.iter().map(AsRef::as_ref).collect();
+    // The index is built once and shared by all files
+    let citation_index = build_citation_index(all_entries);
+    let verify_file = |mdx_path: &String| {
+        verify_mdx_file(
+            mdx_path,
+            all_entries,
+            &citation_index,
+            settings,
+            citation_regex_override.as_ref(),
+            &citation_regexes,
+        )
+    };
+    // Files are verified concurrently with the `parallel` feature. The outcomes are still
+    // gathered in the order of the paths, so the articles and errors are deterministic.
+    #[cfg(feature = "parallel")]
+    let verifications: Vec<Result<FileVerification, Error>> = {
+        use rayon::prelude::*;
+        mdx_paths.par_iter().map(verify_file).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let verifications = mdx_paths.iter().map(verify_file);
+    for (mdx_path, verification) in mdx_paths.iter().zip(verifications) {
+        match verification? {
+            FileVerification::Skipped => verify_report.total_skipped += 1,
+            FileVerification::Article(article, citations_count) => {
+                verify_report.articles.push(*article);
+                verify_report.total_articles += 1;
+                verify_report.total_citations_matched += citations_count;
             }
-        };
-        if !metadata.is_article {
-            continue;
+            FileVerification::CitationErrors(errors) => citation_errors.extend(
+                errors
+                    .into_iter()
+                    .map(|(line, err)| (mdx_path.clone(), line, err)),
+            ),
         }
-        if !check_parentheses_balance(&markdown_content) {
+    }
+    if !citation_errors.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            CitationErrors(citation_errors),
+        ));
+    }
+    if settings.warn_unused || settings.strict_unused {
+        let unused_keys = find_unused_keys(all_entries, &verify_report.articles);
+        if settings.strict_unused && !unused_keys.is_empty() {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
-                format!("Unbalanced parentheses in {}", mdx_path),
+                format!("Bibliography entries never cited: {:?}", unused_keys),
             ));
         }
-        let citations = extract_citations_from_markdown(&markdown_content);
-        match verify_citations_format(&citations) {
-            Ok(_) => {}
-            Err(err) => {
-                eprintln!("Error verifying citations: {} in {}", err, mdx_path);
-                std::process::exit(1);
+        for key in unused_keys {
+            println!("⚠ Bibliography: {} is never cited", key);
+        }
+    }
+    println!("✓ Integrity verification OK: {}", verify_report);
+    Ok(verify_report)
+}
+
+/// The outcome of verifying a single MDX file.
+enum FileVerification {
+    /// The file is not an article, or is a draft that is skipped.
+    Skipped,
+    /// The file is a verified article, with the number of citations matched in it.
+    Article(Box<ArticleFileData>, usize),
+    /// The citation errors of the file with their lines, collected unless `fail_fast` is set.
+    CitationErrors(Vec<(usize, CitationError)>),
+}
+
+/// Verifies a single MDX file as part of `verify_mdx_files`.
+fn verify_mdx_file(
+    mdx_path: &String,
+    all_entries: &Vec<Entry>,
+    citation_index: &HashMap<String, Vec<&Entry>>,
+    settings: &Settings,
+    citation_regex_override: Option<&Regex>,
+    citation_regexes: &[&Regex],
+) -> Result<FileVerification, Error> {
+    let (mut metadata, markdown_content, full_file_content) = match read_mdx_file(&mdx_path, settings) {
+        Ok(data) => data,
+        Err(err) => {
+            if err.kind() == io::ErrorKind::InvalidData {
+                return Err(io::Error::new(
+                    err.kind(),
+                    format!("Invalid MDX data format: {}", err),
+                ));
+            } else {
+                return Err(io::Error::new(
+                    err.kind(),
+                    format!("Unexpected error reading MDX file {}: {}", mdx_path, err),
+                ));
             }
-        };
-        let citations_set = create_citations_set(citations);
-        let matched_citations = match match_citations_to_bibliography(citations_set, &all_entries) {
-            Ok(data) => data,
-            Err(err) => {
-                eprintln!(
-                    "Error matching citations to bibliography: {} in {}",
-                    err, mdx_path
+        }
+    };
+    if !metadata.is_article {
+        return Ok(FileVerification::Skipped);
+    }
+    if metadata.draft && !settings.include_drafts {
+        println!("Skipping draft article {}", mdx_path);
+        return Ok(FileVerification::Skipped);
+    }
+    if settings.derive_index_title && metadata.index_title.is_none() {
+        metadata.index_title = Some(derive_index_title(&metadata.title));
+    }
+    if settings.lint_index_title {
+        if let Some(index_title) = &metadata.index_title {
+            if titles_differ_trivially(&metadata.title, index_title) {
+                println!(
+                    "⚠ Frontmatter: indexTitle \"{}\" differs from title \"{}\" only by whitespace or case in {}",
+                    index_title, metadata.title, mdx_path
                 );
-                std::process::exit(1);
             }
+        }
+    }
+    if settings.strict_parentheses {
+        if let Err(line) = check_parentheses_balance_per_paragraph(&markdown_content) {
+            let line_offset = frontmatter_line_offset(&full_file_content, &markdown_content);
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Unbalanced parentheses at line {} in {}",
+                    line + line_offset,
+                    mdx_path
+                ),
+            ));
+        }
+    } else if let Err((line, column)) = check_parentheses_balance(&markdown_content) {
+        let line_offset = frontmatter_line_offset(&full_file_content, &markdown_content);
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Unbalanced parentheses at line {}, column {} in {}",
+                line + line_offset,
+                column,
+                mdx_path
+            ),
+        ));
+    }
+    // Sections injected by a previous `process`, such as "Jane Doe (2024)" under the authors, are not cited
+    let body = inserters::strip_injected_content(&markdown_content, settings);
+    let mut citations = match citation_regex_override {
+        Some(citation_regex) => extract_citations_with_regex(&body, citation_regex),
+        None => extract_citations_with_regexes(&body, citation_regexes),
+    };
+    // Report the lines of the citations as lines of the file rather than of the markdown
+    let line_offset = frontmatter_line_offset(&full_file_content, &markdown_content);
+    for citation in &mut citations {
+        citation.line += line_offset;
+        if let Some(no_date_token) = &settings.no_date_token {
+            citation.text = normalize_no_date_token(&citation.text, no_date_token);
+        }
+    }
+    let mut classical_citations = Vec::new();
+    if settings.classical_citations {
+        citations.retain(|citation| !is_classical_citation(&citation.text));
+        classical_citations = extract_classical_citations_from_markdown(&markdown_content);
+    }
+    if let Some(threshold) = settings.citation_density_threshold {
+        for (start, end, count) in check_citation_density(&markdown_content, threshold) {
+            println!(
+                "⚠ Citation density: {} citations in lines {}-{} of {}",
+                count,
+                start + line_offset,
+                end + line_offset,
+                mdx_path
+            );
+        }
+    }
+    let citations_count = citations.len() + classical_citations.len();
+    let (mut matched_citations, mut errors) =
+        match match_citations_to_bibliography(&citations, citation_index) {
+            Ok(matched_citations) => (matched_citations, Vec::new()),
+            Err(errors) => (Vec::new(), errors),
         };
-        all_articles.push(ArticleFileData {
+    if settings.strict_locators {
+        errors.extend(collect_locator_errors(&citations));
+        errors.sort_by_key(|(line, _)| *line);
+    }
+    if !errors.is_empty() {
+        // With `fail_fast` the first file with errors fails on its own
+        if settings.fail_fast {
+            let errors = errors
+                .into_iter()
+                .map(|(line, err)| (mdx_path.clone(), line, err))
+                .collect();
+            return Err(io::Error::new(io::ErrorKind::InvalidData, CitationErrors(errors)));
+        }
+        return Ok(FileVerification::CitationErrors(errors));
+    }
+    match match_classical_citations_to_bibliography(classical_citations, &all_entries) {
+        Ok(classical_entries) => {
+            for entry in classical_entries {
+                if !matched_citations.iter().any(|matched| matched.key == entry.key) {
+                    matched_citations.push(entry);
+                }
+            }
+        }
+        Err(err) => {
+            return Err(io::Error::new(
+                err.kind(),
+                format!(
+                    "Error matching citations to bibliography: {} in {}",
+                    err, mdx_path
+                ),
+            ));
+        }
+    };
+    if let Some(allowed_keys) = &settings.allowed_keys {
+        let disallowed_keys = find_disallowed_keys(&matched_citations, allowed_keys);
+        if !disallowed_keys.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Citations of keys outside the allowed keys: ({:?}) in {}",
+                    disallowed_keys, mdx_path
+                ),
+            ));
+        }
+    }
+    if !matched_citations.is_empty()
+        && matched_citations
+            .iter()
+            .all(|entry| !transformers::is_supported_entry_type(&entry.entry_type))
+    {
+        println!(
+            "⚠ All {} works cited in {} have unsupported entry types and will be omitted from its bibliography",
+            matched_citations.len(),
+            mdx_path
+        );
+    }
+    Ok(FileVerification::Article(
+        Box::new(ArticleFileData {
             path: mdx_path.clone(),
             metadata,
             markdown_content,
             matched_citations,
             full_file_content,
-        });
-        article_count += 1;
-    }
-    println!(
-        "✓ Integrity verification OK: {} files verified, including {} articles",
-        mdx_paths.len(),
-        article_count
-    );
-    Ok(all_articles)
+        }),
+        citations_count,
+    ))
 }
 
 /// Reads an MDX file and extracts metadata and markdown content.
 /// The function returns a tuple containing the metadata, markdown content, and full file content.
-/// The metadata is expected to be enclosed in `---` at the start of the file.
-fn read_mdx_file(path: &str) -> io::Result<(Metadata, String, String)> {
+/// The metadata is expected to be enclosed in `---` (YAML) or `+++` (TOML) at the start of the file,
+/// and to have the fields required by the settings.
+fn read_mdx_file(path: &str, settings: &Settings) -> io::Result<(Metadata, String, String)> {
     let file = fs::File::open(path)?;
     let mut reader = BufReader::new(file);
     let mut content = String::new();
     reader.read_to_string(&mut content)?;
 
-    // Extract metadata enclosed in `---` at the start of the file
-    let parts: Vec<&str> = content.splitn(3, "---").collect();
-    if parts.len() != 3 {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("Unable to extract metadata in {}", path),
-        ));
-    }
-
-    let metadata_str = parts[1];
-    let metadata: Metadata = match serde_yaml::from_str(metadata_str) {
-        Ok(data) => data,
-        Err(err) => {
+    // Extract metadata enclosed in `---` or `+++` at the start of the file
+    let (metadata_str, markdown_str) = match split_frontmatter(&content) {
+        Some(parts) => parts,
+        None => {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
-                format!("{} in {}", err, path),
+                format!("Unable to extract metadata in {}", path),
             ))
         }
     };
-    let markdown_content = parts[2].to_string();
+
+    let metadata = deserialize_metadata(metadata_str, content.starts_with("+++"), path, settings)?;
+    let markdown_content = markdown_str.to_string();
     let full_file_content = content.clone();
 
     Ok((metadata, markdown_content, full_file_content))
 }
 
+/// Frontmatter fields every file must have, unless `required_metadata` is set.
+pub const DEFAULT_REQUIRED_METADATA: [&str; 3] = ["title", "description", "isArticle"];
+
+/// Deserializes the frontmatter of the file at the path, TOML if `is_toml` and YAML otherwise.
+/// Fails with one line per required field that is missing, before any other error of the frontmatter.
+fn deserialize_metadata(
+    metadata_str: &str,
+    is_toml: bool,
+    path: &str,
+    settings: &Settings,
+) -> io::Result<Metadata> {
+    let invalid_data =
+        |err: String| io::Error::new(io::ErrorKind::InvalidData, format!("{} in {}", err, path));
+
+    // Hugo-style frontmatter fenced by `+++` is TOML
+    let parsed_frontmatter: Result<serde_yaml::Value, String> = if is_toml {
+        toml::from_str(metadata_str).map_err(|err| err.to_string())
+    } else {
+        serde_yaml::from_str(metadata_str).map_err(|err| err.to_string())
+    };
+    let frontmatter = parsed_frontmatter.map_err(invalid_data)?;
+
+    let required_fields: Vec<&str> = match &settings.required_metadata {
+        Some(fields) => fields.iter().map(String::as_str).collect(),
+        None => DEFAULT_REQUIRED_METADATA.to_vec(),
+    };
+    let missing_fields = find_missing_metadata(&frontmatter, &required_fields);
+    if !missing_fields.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            missing_fields
+                .iter()
+                .map(|field| format!("Missing required frontmatter field `{}` in {}", field, path))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ));
+    }
+
+    serde_yaml::from_value(frontmatter).map_err(|err| invalid_data(err.to_string()))
+}
+
+/// Required fields absent from the frontmatter, or left empty, in the order they are required.
+fn find_missing_metadata<'a>(frontmatter: &serde_yaml::Value, required_fields: &[&'a str]) -> Vec<&'a str> {
+    required_fields
+        .iter()
+        .filter(|field| match frontmatter.get(**field) {
+            Some(value) => value.is_null(),
+            None => true,
+        })
+        .copied()
+        .collect()
+}
+
+/// Splits the content of an MDX file into its frontmatter and markdown.
+/// The frontmatter must open the file with a `---` line, or a `+++` line for TOML frontmatter,
+/// and is closed by the next line that is exactly the same fence, so indented dashes
+/// inside YAML block scalars are left alone. The markdown is everything after the closing fence.
+fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let first_line_end = content.find('\n')?;
+    let fence = content[..first_line_end].trim_end();
+    if fence != "---" && fence != "+++" {
+        return None;
+    }
+
+    let mut offset = first_line_end + 1;
+    for line in content[offset..].split_inclusive('\n') {
+        if line.trim_end() == fence {
+            let metadata = &content[first_line_end + 1..offset];
+            let markdown = &content[offset + fence.len()..];
+            return Some((metadata, markdown));
+        }
+        offset += line.len();
+    }
+    None
+}
+
 /// Checks if the parentheses in a markdown string are balanced. 
 /// No odd number of parentheses is allowed.
-fn check_parentheses_balance(markdown: &String) -> bool {
-    let mut balance = 0;
+/// Returns the 1-based line and column of the first closing parenthesis without an opening one,
+/// or otherwise of the first opening parenthesis that is never closed.
+fn check_parentheses_balance(markdown: &String) -> Result<(), (usize, usize)> {
+    let mut open_positions: Vec<(usize, usize)> = Vec::new();
+
+    for (index, line) in markdown.lines().enumerate() {
+        for (column, ch) in line.chars().enumerate() {
+            let position = (index + 1, column + 1);
+            if ch == '(' {
+                open_positions.push(position);
+            } else if ch == ')' && open_positions.pop().is_none() {
+                return Err(position);
+            }
+        }
+    }
+
+    match open_positions.first() {
+        Some(position) => Err(*position),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the parentheses are balanced within every paragraph, paragraphs being
+/// separated by blank lines, so that an unclosed parenthesis and a stray closing one
+/// further down do not cancel each other out.
+/// Returns the 1-based line of the first unbalanced parenthesis.
+fn check_parentheses_balance_per_paragraph(markdown: &String) -> Result<(), usize> {
+    let mut open_lines: Vec<usize> = Vec::new();
 
-    for ch in markdown.chars() {
-        if ch == '(' {
-            balance += 1;
-        } else if ch == ')' {
-            balance -= 1;
+    for (index, line) in markdown.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            if let Some(open_line) = open_lines.first() {
+                return Err(*open_line);
+            }
+            continue;
         }
+        for ch in line.chars() {
+            if ch == '(' {
+                open_lines.push(line_number);
+            } else if ch == ')' && open_lines.pop().is_none() {
+                return Err(line_number);
+            }
+        }
+    }
+
+    match open_lines.first() {
+        Some(open_line) => Err(*open_line),
+        None => Ok(()),
+    }
+}
+
+/// Number of lines that precede the markdown content in the full file,
+/// used to translate line numbers in the markdown into line numbers in the file.
+fn frontmatter_line_offset(full_file_content: &String, markdown: &String) -> usize {
+    full_file_content.lines().count() - markdown.lines().count()
+}
 
-        if balance < 0 {
-            return false;
+/// Checks the number of citations per paragraph, paragraphs being separated by blank lines.
+/// Returns the 1-based start line, end line and citation count of every paragraph
+/// with more citations than the threshold.
+fn check_citation_density(markdown: &String, threshold: usize) -> Vec<(usize, usize, usize)> {
+    let mut paragraphs: Vec<(usize, usize, String)> = Vec::new();
+    let mut current: Option<(usize, usize, String)> = None;
+
+    for (index, line) in markdown.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            if let Some(paragraph) = current.take() {
+                paragraphs.push(paragraph);
+            }
+            continue;
+        }
+        match current.as_mut() {
+            Some((_, end, text)) => {
+                *end = line_number;
+                text.push('\n');
+                text.push_str(line);
+            }
+            None => current = Some((line_number, line_number, line.to_string())),
         }
     }
+    if let Some(paragraph) = current.take() {
+        paragraphs.push(paragraph);
+    }
 
-    balance == 0
+    paragraphs
+        .into_iter()
+        .filter_map(|(start, end, text)| {
+            let count = extract_citations_from_markdown(&text).len();
+            if count > threshold {
+                Some((start, end, count))
+            } else {
+                None
+            }
+        })
+        .collect()
 }
 
 /// Extract citations from a markdown string
 /// The citations are assumed to be Chicago author-date style
-/// and in the format (Author_last_name 2021) or (Author_last_name 2021, 123)
+/// and in the format (Author_last_name 2021) or (Author_last_name 2021, 123).
+/// Works without an author are cited by their italicized short title.
+/// Narrative citations, as in "Hegel (2021, 123) argues", are extracted as "Hegel 2021, 123".
+/// A signal phrase opening the citation, such as "see also" or "cf.", is not part of the citation.
 ///
 /// ### Example
 ///
-/// (Hegel 2021) or (Hegel 2021, 123)
-fn extract_citations_from_markdown(markdown: &String) -> Vec<String> {
+/// (Hegel 2021) or (Hegel 2021, 123) or (_Beowulf_ 2000, 3) or Hegel (2021, 123) or (cf. Hegel 2021)
+fn extract_citations_from_markdown(markdown: &String) -> Vec<Citation> {
+    extract_citations_with_regexes(markdown, &default_citation_regexes())
+}
+
+/// Signal phrases a parenthetical citation may open with, e.g. "(cf. Hegel 2010)".
+const DEFAULT_CITATION_PREFIXES: [&str; 4] = ["see", "see also", "cf.", "e.g.,"];
+
+/// The built-in regexes for parenthetical and narrative citations.
+fn default_citation_regexes() -> [&'static Regex; 2] {
+    static CITATION_REGEX: OnceLock<Regex> = OnceLock::new();
+    let citation_regex = CITATION_REGEX.get_or_init(|| {
+        let prefixes: Vec<String> = DEFAULT_CITATION_PREFIXES.iter().map(|p| p.to_string()).collect();
+        build_citation_regex(&prefixes, DEFAULT_NO_DATE_TOKEN)
+    });
+    static NARRATIVE_CITATION_REGEX: OnceLock<Regex> = OnceLock::new();
+    let narrative_citation_regex = NARRATIVE_CITATION_REGEX
+        .get_or_init(|| build_narrative_citation_regex(DEFAULT_NO_DATE_TOKEN));
+    [citation_regex, narrative_citation_regex]
+}
+
+/// The regexes for parenthetical and narrative citations, with the signal phrases
+/// and the no-date token of the settings if configured, or the built-in ones otherwise.
+fn citation_regexes_for(settings: &Settings) -> Vec<Cow<'static, Regex>> {
+    if settings.citation_prefixes.is_none() && settings.no_date_token.is_none() {
+        return default_citation_regexes().into_iter().map(Cow::Borrowed).collect();
+    }
+    let no_date_token = settings.no_date_token.as_deref().unwrap_or(DEFAULT_NO_DATE_TOKEN);
+    vec![
+        Cow::Owned(build_citation_regex(&citation_prefixes_for(settings), no_date_token)),
+        Cow::Owned(build_narrative_citation_regex(no_date_token)),
+    ]
+}
+
+/// The signal phrases of the settings if configured, or the built-in ones otherwise.
+fn citation_prefixes_for(settings: &Settings) -> Vec<String> {
+    settings.citation_prefixes.clone().unwrap_or_else(|| {
+        DEFAULT_CITATION_PREFIXES.iter().map(|p| p.to_string()).collect()
+    })
+}
+
+/// Builds the regex for parenthetical citations opening with any of the given signal phrases,
+/// which are matched literally, longest first, with any whitespace between their words.
+/// Undated works are cited with the no-date token in place of the year, e.g. "(Hegel n.d.)".
+fn build_citation_regex(prefixes: &[String], no_date_token: &str) -> Regex {
+    let mut prefixes: Vec<String> = prefixes
+        .iter()
+        .map(|prefix| {
+            prefix
+                .split_whitespace()
+                .map(regex::escape)
+                .collect::<Vec<_>>()
+                .join(r"\s+")
+        })
+        .filter(|prefix| !prefix.is_empty())
+        .collect();
+    prefixes.sort_by_key(|prefix| std::cmp::Reverse(prefix.len()));
     //      Regex explanation
     //
     //      \(      Match an opening parenthesis
-    //     (see\s)? Optionally match the word "see" followed by a whitespace
-    //      ([A-Z]  Match a capital letter
+    //      (?:(?:see|cf\.|...)\s)?  Optionally match a signal phrase followed by a whitespace
+    //      ((?:    Start the citation and a non-capturing group for its lead
+    //      [A-Z]   Match a capital letter
     //      [^()]*? Match any character except opening and closing parenthesis
-    //      \d+     Match one or more digits
+    //      |       Or, for anonymous works cited by title
+    //      _[^_()]+_\s  Match an italicized title followed by a whitespace
+    //      )       End the non-capturing group
+    //      (?:\d+|n\.d\.)  Match one or more digits, or the no-date token
     //      (?:     Start a non-capturing group
     //      ,       Match a comma
     //      [^)]*   Match any character except closing parenthesis
     //      )?      End the non-capturing group and make it optional
+    //      \.?     Optionally match a stray period
     //      \)      Match a closing parenthesis
     //
     // The regex will match citations in the format (Author_last_name 2021) or (Author_last_name 2021, 123)
+    // as well as (_Short Title_ 2021) for works without an author.
+    // Stray trailing punctuation, as in (Hegel 2021.) or (Hegel 2021,), is trimmed from the citation.
+    //
+    let prefix_pattern = if prefixes.is_empty() {
+        String::new()
+    } else {
+        format!(r"(?:(?:{})\s)?", prefixes.join("|"))
+    };
+    Regex::new(&format!(
+        r"\({}((?:[A-Z][^()]*?|_[^_()]+_\s)(?:\d+|{})(?:,[^)]*)?\.?)\)",
+        prefix_pattern,
+        regex::escape(no_date_token)
+    ))
+    .unwrap()
+}
+
+/// Builds the regex for narrative citations, as in "Hegel (2021, 123) argues" or "Hegel (n.d.) argues".
+/// Works by several authors are cited as in "Marx and Engels (1848)" or "Marx et al. (1848)".
+fn build_narrative_citation_regex(no_date_token: &str) -> Regex {
+    //      Regex explanation for narrative citations
+    //
+    //      \b                  Start at a word boundary
+    //      (?P<author>         Capture the author's last name
+    //      [A-Z][\p{L}'’-]*    Match a capital letter followed by letters, apostrophes or hyphens
+    //      (?:\sand\s[A-Z][\p{L}'’-]*|\set\sal\.)?  Optionally match a second author or "et al."
+    //      )                   End the author
+    //      \s\(                Match a whitespace and an opening parenthesis
+    //      (?P<year>\d{4}|n\.d\.)  Capture the year, or the no-date token
+    //      (?P<locator>        Capture the optional locator
+    //      ,[^()]*             Match a comma and any character except parentheses
+    //      )?                  End the locator and make it optional
+    //      \)                  Match a closing parenthesis
+    //
+    // Life dates, as in Hegel (1770–1831), are not matched as the year must be followed by a comma or ")".
     //
-    let citation_regex = Regex::new(r"\((see\s)?([A-Z][^()]*?\d+(?:,[^)]*)?)\)").unwrap();
+    Regex::new(&format!(
+        r"\b(?P<author>[A-Z][\p{{L}}'’-]*(?:\sand\s[A-Z][\p{{L}}'’-]*|\set\sal\.)?)\s\((?P<year>\d{{4}}|{})(?P<locator>,[^()]*)?\)",
+        regex::escape(no_date_token)
+    ))
+    .unwrap()
+}
+
+/// Extract citations from a markdown string with the given regex, e.g. a user-supplied override.
+/// The citation is taken from the capture group named `citation` if there is one,
+/// otherwise from the last capture group that participated in the match.
+/// It must be in the "Author_last_name 2021, 123" form, without the parentheses.
+fn extract_citations_with_regex(markdown: &String, citation_regex: &Regex) -> Vec<Citation> {
+    extract_citations_with_regexes(markdown, &[citation_regex])
+}
+
+/// Extract citations from a markdown string with several regexes,
+/// keeping the citations of each line in the order they appear in.
+/// Narrative citations are assembled from the `author`, `year` and `locator` capture groups.
+fn extract_citations_with_regexes(markdown: &String, citation_regexes: &[&Regex]) -> Vec<Citation> {
     let mut citations = Vec::new();
 
-    for line in markdown.lines() {
-        for captures in citation_regex.captures_iter(line) {
-            match captures.len() {
-                2 => {
-                    let citation = captures.get(1).unwrap().as_str().trim();
-                    citations.push(citation.to_string());
-                },
-                3 => {
-                    let citation = captures.get(2).unwrap().as_str().trim();
-                    citations.push(citation.to_string());
-                },
-                _ => {} // Ignore unexpected capture group lengths
+    for (index, line) in markdown.lines().enumerate() {
+        let line = mask_inline_code(line);
+        let mut line_citations: Vec<(usize, String)> = Vec::new();
+        for citation_regex in citation_regexes {
+            for captures in citation_regex.captures_iter(&line) {
+                let start = captures.get(0).map_or(0, |matched| matched.start());
+                if let Some(citation) = citation_from_captures(&captures) {
+                    line_citations.push((start, citation));
+                }
             }
         }
+        line_citations.sort_by_key(|(start, _)| *start);
+        for (_, citation) in line_citations {
+            citations.push(Citation {
+                text: trim_stray_punctuation(&citation).to_string(),
+                line: index + 1,
+            });
+        }
     }
     citations
 }
 
-/// Verifies the format of the citations extracted from the markdown.
-/// The citations are expected to be in the format (Author_last_name 2021) 
-/// or (Author_last_name 2021, 123)
-fn verify_citations_format(citations: &Vec<String>) -> Result<(), io::Error> {
-    for citation in citations {
-        let citation_split = citation.splitn(2, ',').collect::<Vec<&str>>();
-        let first_part = citation_split[0].trim();
-        let has_year = first_part.split_whitespace().any(|word| {
-            if let Ok(num) = word.parse::<u32>() {
-                num >= 1000 && num <= 9999
-            } else {
-                false
+/// The citation of a regex match, assembled from the `author`, `year` and `locator` capture groups
+/// of a narrative citation, or otherwise taken from the `citation` or the last participating group.
+fn citation_from_captures(captures: &regex::Captures) -> Option<String> {
+    match (captures.name("author"), captures.name("year")) {
+        (Some(author), Some(year)) => Some(format!(
+            "{} {}{}",
+            author.as_str(),
+            year.as_str(),
+            captures.name("locator").map_or("", |locator| locator.as_str())
+        )),
+        _ => captures
+            .name("citation")
+            .or_else(|| captures.iter().skip(1).flatten().last())
+            .map(|citation| citation.as_str().to_string()),
+    }
+}
+
+/// Rewrites the citations in the markdown of an MDX file into links to the bibliography anchors
+/// of the entries they cite, e.g. "(Hegel 2010, 61)" into "[(Hegel 2010, 61)](#cite-hegel2010logic)".
+/// The citations are looked up in a prebuilt index of the bibliography and only linked to the linked entries.
+/// The frontmatter is left alone, as are citations inside inline code, citations matching none of
+/// the linked entries and citations that are already linked, so that linking a file twice changes nothing.
+/// The citation stays the text of the link, so that linked citations are still verified.
+pub fn link_citations_in_mdx(
+    content: &str,
+    citation_index: &HashMap<String, Vec<&Entry>>,
+    linked_entries: &Vec<Entry>,
+    settings: &Settings,
+) -> String {
+    let markdown = split_frontmatter(content).map_or(content, |(_, markdown)| markdown);
+    let frontmatter = &content[..content.len() - markdown.len()];
+    let citation_regexes = citation_regexes_for(settings);
+    let mut linked_content = frontmatter.to_string();
+
+    for line in markdown.split_inclusive('\n') {
+        let masked_line = mask_inline_code(line);
+        let mut citation_spans: Vec<(usize, usize, String)> = Vec::new();
+        for citation_regex in &citation_regexes {
+            for captures in citation_regex.captures_iter(&masked_line) {
+                let matched = captures.get(0).unwrap();
+                let citation = match citation_from_captures(&captures) {
+                    Some(citation) => citation,
+                    None => continue,
+                };
+                let is_linked = line[..matched.start()].ends_with('[')
+                    && line[matched.end()..].starts_with("](");
+                if is_linked {
+                    continue;
+                }
+                let citation = match &settings.no_date_token {
+                    Some(no_date_token) => normalize_no_date_token(&citation, no_date_token),
+                    None => citation,
+                };
+                let citation = trim_stray_punctuation(&citation);
+                let key = match match_citation_in_index(citation, citation_index) {
+                    Ok(outcome) if !outcome.is_ambiguous() => outcome.entries[0].key.clone(),
+                    _ => continue,
+                };
+                if !linked_entries.iter().any(|entry| entry.key == key) {
+                    continue;
+                }
+                citation_spans.push((matched.start(), matched.end(), key));
             }
-        });
-        if !has_year {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Citation is malformed or is missing year: ({})", citation),
+        }
+        citation_spans.sort_by_key(|(start, _, _)| *start);
+
+        let mut position = 0;
+        for (start, end, key) in citation_spans {
+            // Spans matched by both regexes are only linked once
+            if start < position {
+                continue;
+            }
+            linked_content.push_str(&line[position..start]);
+            linked_content.push_str(&format!(
+                "[{}](#{})",
+                &line[start..end],
+                inserters::citation_anchor_id(&key)
             ));
+            position = end;
         }
+        linked_content.push_str(&line[position..]);
     }
-    Ok(())
+    linked_content
 }
 
-/// Creates a set of unique citations from a list of citations.
-fn create_citations_set(citations: Vec<String>) -> Vec<String> {
-    let mut citations_set = Vec::new();
-    for citation in citations {
-        let prepared_citation = citation
-            .splitn(2, ',')
-            .next()
-            .unwrap_or(&citation)
-            .to_string();
-        if !citations_set.contains(&prepared_citation) {
-            citations_set.push(prepared_citation);
+/// Mask inline code spans, such as `` `see (Hegel 2010)` ``, with spaces so that citations
+/// inside them are not extracted. A span opened by a run of backticks is only closed by a run
+/// of the same length, escaped backticks do not open a span, and unclosed backticks are kept as is.
+/// Each byte of a span is masked by a space so that match offsets carry over to the original line.
+fn mask_inline_code(line: &str) -> String {
+    let bytes = line.as_bytes();
+    let backtick_run = |start: usize| bytes[start..].iter().take_while(|&&b| b == b'`').count();
+    let mut masked = String::with_capacity(line.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'`') {
+            masked.push_str("\\`");
+            i += 2;
+            continue;
+        }
+        if bytes[i] == b'`' {
+            let run = backtick_run(i);
+            let mut j = i + run;
+            let mut span_end = None;
+            while j < bytes.len() {
+                if bytes[j] == b'`' {
+                    let closing_run = backtick_run(j);
+                    if closing_run == run {
+                        span_end = Some(j + closing_run);
+                        break;
+                    }
+                    j += closing_run;
+                } else {
+                    j += 1;
+                }
+            }
+            match span_end {
+                Some(end) => {
+                    masked.push_str(&" ".repeat(end - i));
+                    i = end;
+                }
+                None => {
+                    masked.push_str(&line[i..i + run]);
+                    i += run;
+                }
+            }
+            continue;
         }
+        let ch = line[i..].chars().next().unwrap();
+        masked.push(ch);
+        i += ch.len_utf8();
     }
-    citations_set
+    masked
 }
 
-/// Matches citations to the inputted bibliography
-/// the matched list is returned with full bibliographical details.
-/// If any citation is not found in the bibliography, an error is returned.
-fn match_citations_to_bibliography(
+/// Trim stray trailing periods and commas from a citation,
+/// e.g. "Hegel 2020." and "Hegel 2020," both become "Hegel 2020".
+/// The period closing an abbreviation such as "n.d." is kept, e.g. in "Hegel n.d.".
+fn trim_stray_punctuation(citation: &str) -> &str {
+    let citation = citation.trim();
+    let trimmed = citation.trim_end_matches(|c: char| c == '.' || c == ',' || c.is_whitespace());
+    let last_word = trimmed.rsplit(char::is_whitespace).next().unwrap_or(trimmed);
+    if last_word.contains('.') && citation[trimmed.len()..].starts_with('.') {
+        &citation[..trimmed.len() + 1]
+    } else {
+        trimmed
+    }
+}
+
+/// Replaces a configured no-date token, e.g. "o.J.", in the year slot of a citation
+/// with "n.d.", the form undated entries are matched by.
+fn normalize_no_date_token(citation: &str, no_date_token: &str) -> String {
+    let (first_part, locator) = citation.split_at(citation.find(',').unwrap_or(citation.len()));
+    let first_part = first_part
+        .split_whitespace()
+        .map(|word| if word == no_date_token { DEFAULT_NO_DATE_TOKEN } else { word })
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("{}{}", first_part, locator)
+}
+
+/// Locator of a classical citation: a Stephanus number such as "514a",
+/// or a Bekker number such as "1094a1", optionally as a range such as "514a–517c".
+const CLASSICAL_LOCATOR: &str = r"\d+[a-e]\d*(?:[–-]\d*[a-e]?\d*)?";
+
+/// Extract citations of classical works from a markdown string.
+/// These are cited by author, optionally followed by the work, and a classical locator.
+///
+/// ### Example
+///
+/// (Plato, Republic 514a) or (Aristotle 1094a1)
+fn extract_classical_citations_from_markdown(markdown: &String) -> Vec<String> {
+    static CLASSICAL_REGEX: OnceLock<Regex> = OnceLock::new();
+    let classical_regex = CLASSICAL_REGEX.get_or_init(|| {
+        Regex::new(&format!(r"\((?:see\s)?([A-Z][^()]*?\s{})\)", CLASSICAL_LOCATOR)).unwrap()
+    });
+    let mut citations = Vec::new();
+
+    for line in markdown.lines() {
+        let line = mask_inline_code(line);
+        for captures in classical_regex.captures_iter(&line) {
+            citations.push(captures[1].trim().to_string());
+        }
+    }
+    citations
+}
+
+/// Whether an extracted citation ends in a classical locator instead of a year.
+fn is_classical_citation(citation: &str) -> bool {
+    static CLASSICAL_CITATION_REGEX: OnceLock<Regex> = OnceLock::new();
+    let classical_regex = CLASSICAL_CITATION_REGEX
+        .get_or_init(|| Regex::new(&format!(r"^[A-Z][^()]*?\s{}$", CLASSICAL_LOCATOR)).unwrap());
+    classical_regex.is_match(citation)
+}
+
+/// Splits a classical citation into its author, optional work, and locator.
+/// E.g. "Plato, Republic 514a" becomes ("Plato", Some("Republic"), "514a").
+fn split_classical_citation(citation: &str) -> (&str, Option<&str>, &str) {
+    let (reference, locator) = citation.rsplit_once(' ').unwrap_or((citation, ""));
+    match reference.split_once(',') {
+        Some((author, work)) => (author.trim(), Some(work.trim()), locator),
+        None => (reference.trim(), None, locator),
+    }
+}
+
+/// Matches classical citations to the classical works in the bibliography,
+/// i.e. entries with `entrysubtype = {classical}`, by author and, if given, by work.
+/// The work is compared against the short title and the title of the entry.
+fn match_classical_citations_to_bibliography(
     citations: Vec<String>,
     bibliography: &Vec<Entry>,
 ) -> Result<Vec<Entry>, io::Error> {
-    let mut unmatched_citations = citations.clone();
-    let mut matched_citations = Vec::new();
+    let mut unmatched_citations = Vec::new();
+    let mut matched_citations: Vec<Entry> = Vec::new();
 
     for citation in citations {
-        for entry in bibliography {
-            let author = entry.author().unwrap();
-            let author_last_name = author[0].name.clone();
-
-            let date: biblatex::PermissiveType<biblatex::Date> = entry.date().unwrap();
-            let year = BiblatexUtils::extract_year_from_date(&date, citation.clone()).unwrap();
-
-            let author_year = format!("{} {:?}", author_last_name, year);
-
-            if citation == author_year {
-                unmatched_citations.retain(|x| x != &citation);
-                matched_citations.push(entry.clone());
+        let (author, work, _locator) = split_classical_citation(&citation);
+        let matched_entry = bibliography.iter().find(|entry| {
+            if !BiblatexUtils::is_classical(entry) {
+                return false;
+            }
+            let author_matches = entry
+                .author()
+                .map(|persons| persons.first().map(|p| p.name == author).unwrap_or(false))
+                .unwrap_or(false);
+            let work_matches = match work {
+                Some(work) => ["shorttitle", "title"].iter().any(|field| {
+                    entry
+                        .get(field)
+                        .map(|title| BiblatexUtils::extract_spanned_chunk(title) == work)
+                        .unwrap_or(false)
+                }),
+                None => true,
+            };
+            author_matches && work_matches
+        });
+        match matched_entry {
+            Some(entry) => {
+                if !matched_citations.iter().any(|matched| matched.key == entry.key) {
+                    matched_citations.push(entry.clone());
+                }
             }
+            None => unmatched_citations.push(citation),
         }
     }
 
-    if unmatched_citations.len() > 0 {
+    if !unmatched_citations.is_empty() {
         return Err(io::Error::new(
             io::ErrorKind::InvalidData,
             format!(
-                "Citations not found in the library: ({:?})",
+                "Classical citations not found in the library: ({:?})",
                 unmatched_citations
             ),
         ));
@@ -272,25 +1047,1176 @@ fn match_citations_to_bibliography(
     Ok(matched_citations)
 }
 
-#[cfg(test)]
-mod tests_balanced_parentheses {
-    use super::*;
+/// Keys of the matched entries that are not in the list of allowed keys.
+fn find_disallowed_keys(matched_citations: &Vec<Entry>, allowed_keys: &Vec<String>) -> Vec<String> {
+    matched_citations
+        .iter()
+        .filter(|entry| !allowed_keys.contains(&entry.key))
+        .map(|entry| entry.key.clone())
+        .collect()
+}
 
-    #[test]
-    fn balanced_parentheses() {
-        let markdown = String::from("This is a balanced citation (Spinoza 2021).");
-        assert!(check_parentheses_balance(&markdown));
-    }
-    #[test]
-    fn unbalanced_parentheses_more_open() {
-        let markdown = String::from("This is an unbalanced citation (Spinoza 2021.");
-        assert!(!check_parentheses_balance(&markdown));
-    }
-    #[test]
-    fn unbalanced_parentheses_more_close() {
-        let markdown = String::from("This is an unbalanced citation Spinoza 2021).");
-        assert!(!check_parentheses_balance(&markdown));
-    }
+/// Keys of the bibliography entries not matched by any citation of the articles.
+fn find_unused_keys(all_entries: &Vec<Entry>, articles: &Vec<ArticleFileData>) -> Vec<String> {
+    all_entries
+        .iter()
+        .filter(|entry| {
+            !articles
+                .iter()
+                .any(|article| article.matched_citations.iter().any(|cited| cited.key == entry.key))
+        })
+        .map(|entry| entry.key.clone())
+        .collect()
+}
+
+/// Derives an index title from the title of an article, trimming and collapsing its whitespace,
+/// e.g. " Science  of Logic " becomes "Science of Logic".
+fn derive_index_title(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Whether two titles differ only by whitespace or case, which is likely a typo
+/// rather than an intentionally different index title.
+fn titles_differ_trivially(title: &str, index_title: &str) -> bool {
+    let normalize = |text: &str| {
+        text.split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    title != index_title && normalize(title) == normalize(index_title)
+}
+
+/// Verifies the format of the citations extracted from the markdown.
+/// The citations are expected to be in the format (Author_last_name 2021) 
+/// or (Author_last_name 2021, 123)
+fn verify_citations_format(citations: &Vec<Citation>) -> Result<(), io::Error> {
+    for citation in citations {
+        let citation_split = citation.text.splitn(2, ',').collect::<Vec<&str>>();
+        let first_part = citation_split[0].trim();
+        let has_year = first_part.split_whitespace().any(|word| {
+            if let Ok(num) = word.parse::<u32>() {
+                num >= 1000 && num <= 9999
+            } else {
+                word == DEFAULT_NO_DATE_TOKEN
+            }
+        });
+        if !has_year {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Citation is malformed or is missing year at line {}: ({})",
+                    citation.line, citation.text
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the locator of every citation, i.e. everything after the first comma, and collects
+/// the citations with an invalid one, each paired with its line.
+/// Citations without a locator are fine.
+fn collect_locator_errors(citations: &Vec<Citation>) -> Vec<(usize, CitationError)> {
+    citations
+        .iter()
+        .filter_map(|citation| {
+            let (_, locator) = citation.text.split_once(',')?;
+            let locator = locator.trim();
+            if is_valid_locator(locator) {
+                return None;
+            }
+            Some((
+                citation.line,
+                CitationError::InvalidLocator(citation.text.clone(), locator.to_string()),
+            ))
+        })
+        .collect()
+}
+
+/// Whether a locator is made of pages, page ranges or recognized locators separated by commas,
+/// e.g. "61", "123–45", "xii", "chap. 3", "§ 12", "n. 4", "61n3" or "61, 63–64".
+/// Pages of multi-volume works are prefixed by the volume, e.g. "1:61" or "2:12–15".
+fn is_valid_locator(locator: &str) -> bool {
+    //      Regex explanation
+    //
+    //      ^                       Start of a locator part
+    //      (?:(?:chap\.|§§?|nn?\.)\s?)?  Optionally match a locator keyword, e.g. "chap. " or "§"
+    //      (?:\d+:)?               Optionally match the volume of a multi-volume work, e.g. "1:"
+    //      (?:\d+|[ivxlcdm]+)      Match a page or a section number, in arabic or lowercase roman numerals
+    //      [a-z]?                  Optionally match a column or a subsection, e.g. "12a"
+    //      (?:\s?[–-]\s?(?:\d+|[ivxlcdm]+)[a-z]?)?  Optionally match the end of a range
+    //      (?:\s?nn?\.?\s?\d+)?     Optionally match a note on the page, e.g. "61n3" or "61 n. 3"
+    //      (?:\s?ff?\.?)?          Optionally match "f." or "ff." for the following pages
+    //      $                       End of the locator part
+    //
+    static LOCATOR_REGEX: OnceLock<Regex> = OnceLock::new();
+    let locator_regex = LOCATOR_REGEX.get_or_init(|| {
+        Regex::new(
+            r"^(?:(?:chap\.|§§?|nn?\.)\s?)?(?:\d+:)?(?:\d+|[ivxlcdm]+)[a-z]?(?:\s?[–-]\s?(?:\d+|[ivxlcdm]+)[a-z]?)?(?:\s?nn?\.?\s?\d+)?(?:\s?ff?\.?)?$",
+        )
+        .unwrap()
+    });
+    locator
+        .split(',')
+        .all(|part| locator_regex.is_match(&normalize_typography(part.trim())))
+}
+
+/// Creates a set of unique citations from a list of citations.
+/// Each citation keeps the line it first appears on.
+fn create_citations_set(citations: Vec<Citation>) -> Vec<Citation> {
+    let mut citations_set: Vec<Citation> = Vec::new();
+    for citation in citations {
+        let prepared_citation = citation
+            .text
+            .splitn(2, ',')
+            .next()
+            .unwrap_or(&citation.text)
+            .to_string();
+        if !citations_set.iter().any(|c| c.text == prepared_citation) {
+            citations_set.push(Citation {
+                text: prepared_citation,
+                line: citation.line,
+            });
+        }
+    }
+    citations_set
+}
+
+/// Matches citations to the bibliography through its prebuilt index
+/// the matched list is returned with full bibliographical details, each entry once.
+/// If any citation is not found in the bibliography, its error is returned along with
+/// the errors of all other citations, each paired with the line of the first citation raising it.
+/// A citation matching more than one entry is ambiguous and returns
+/// `CitationError::AmbiguousMatch` listing the candidate keys.
+fn match_citations_to_bibliography(
+    citations: &Vec<Citation>,
+    citation_index: &HashMap<String, Vec<&Entry>>,
+) -> Result<Vec<Entry>, Vec<(usize, CitationError)>> {
+    let mut errors: Vec<(usize, CitationError)> = Vec::new();
+    let mut matched_citations: Vec<Entry> = Vec::new();
+
+    for citation in citations {
+        let error = match match_citation_in_index(&citation.text, citation_index) {
+            Ok(outcome) if outcome.is_ambiguous() => {
+                let keys = outcome.entries.iter().map(|entry| entry.key.clone()).collect();
+                CitationError::AmbiguousMatch(outcome.citation, keys)
+            }
+            Ok(mut outcome) => {
+                let entry = outcome.entries.remove(0);
+                if !matched_citations.iter().any(|matched| matched.key == entry.key) {
+                    matched_citations.push(entry);
+                }
+                continue;
+            }
+            Err(err) => err,
+        };
+        if !errors.iter().any(|(_, existing)| existing == &error) {
+            errors.push((citation.line, error));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(matched_citations)
+}
+
+/// Largest edit distance at which an unmatched citation is still considered a typo of another.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Suggests the "Author_last_name Year" form of an entry closest to an unmatched citation,
+/// e.g. "Hegel 2010" for "Hegal 2010", if it is within `MAX_SUGGESTION_DISTANCE` edits.
+fn suggest_citation(citation: &str, citation_index: &HashMap<String, Vec<&Entry>>) -> Option<String> {
+    let normalized_citation = normalize_typography(citation);
+    citation_index
+        .keys()
+        // Entry keys have no spaces, unlike the "Author_last_name Year" forms
+        .filter(|author_year| author_year.contains(' '))
+        .map(|author_year| (levenshtein_distance(&normalized_citation, author_year), author_year))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min()
+        .map(|(_, author_year)| author_year.clone())
+}
+
+/// Finds the single entry by the author of an unmatched citation when only the year differs,
+/// e.g. a transcription mistake such as "Hegel 2010" for an entry dated 2011.
+/// Returns the year of the citation and the key and year of the entry, or `None`
+/// if no entry or several entries are by the author.
+fn find_year_mismatch(
+    citation: &str,
+    citation_index: &HashMap<String, Vec<&Entry>>,
+) -> Option<(String, String, String)> {
+    let normalized_citation = normalize_typography(citation);
+    let (author, year) = normalized_citation.rsplit_once(' ')?;
+    let mut candidates: Vec<(&str, &Entry)> = Vec::new();
+    for (author_year, entries) in citation_index {
+        // Entry keys have no spaces, unlike the "Author_last_name Year" forms
+        let (entry_author, entry_year) = match author_year.rsplit_once(' ') {
+            Some(split) => split,
+            None => continue,
+        };
+        if entry_author != author || entry_year == year {
+            continue;
+        }
+        for entry in entries {
+            if !candidates.iter().any(|(_, candidate)| candidate.key == entry.key) {
+                candidates.push((entry_year, entry));
+            }
+        }
+    }
+    match candidates.as_slice() {
+        [(entry_year, entry)] => Some((year.to_string(), entry.key.clone(), entry_year.to_string())),
+        _ => None,
+    }
+}
+
+/// Number of single character insertions, deletions or substitutions turning one text into the other.
+fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current_row = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_row[j] + if a_char == *b_char { 0 } else { 1 };
+            current_row.push(substitution.min(previous_row[j + 1] + 1).min(current_row[j] + 1));
+        }
+        previous_row = current_row;
+    }
+    previous_row[b.len()]
+}
+
+/// Normalize typographic punctuation so that citations match regardless of how an editor
+/// converted it: curly quotes and apostrophes become straight, dashes become hyphens,
+/// and non-breaking spaces become spaces.
+fn normalize_typography(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' | '\u{201B}' | '\u{2032}' => '\'',
+            '\u{201C}' | '\u{201D}' | '\u{201F}' | '\u{2033}' => '"',
+            '\u{2010}'..='\u{2015}' | '\u{2212}' => '-',
+            '\u{00A0}' | '\u{202F}' => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+/// Index of the bibliography entries by their key and by the normalized "Author_last_name Year"
+/// forms they are cited by, so that each citation is looked up instead of scanning the bibliography.
+/// Entries sharing an author and year end up under the same form, which makes the citation ambiguous.
+pub fn build_citation_index(bibliography: &Vec<Entry>) -> HashMap<String, Vec<&Entry>> {
+    let mut citation_index: HashMap<String, Vec<&Entry>> = HashMap::new();
+    for entry in bibliography {
+        citation_index.entry(entry.key.clone()).or_default().push(entry);
+        for author_year in create_author_years(entry) {
+            citation_index
+                .entry(normalize_typography(&author_year))
+                .or_default()
+                .push(entry);
+        }
+    }
+    citation_index
+}
+
+/// Creates the "Author_last_name Year" forms an entry is cited by.
+/// Works by two authors are cited as "Marx and Engels 1848", and by three or more as "Marx et al. 1848",
+/// while the first author alone, as in "Marx 1848", is still accepted for them.
+/// Edited volumes without an author are cited by their editors likewise,
+/// and anonymous works by their italicized short title instead of the author.
+/// Returns no forms for entries that cannot be cited this way.
+fn create_author_years(entry: &Entry) -> Vec<String> {
+    let editors = BiblatexUtils::extract_editors(entry);
+    let last_names: Vec<String> = match entry.author() {
+        Ok(author) if !author.is_empty() => author.into_iter().map(|person| person.name).collect(),
+        // Edited volumes are cited by their editors
+        _ => editors.into_iter().map(|person| person.name).collect(),
+    };
+    let year = match create_citation_year(entry) {
+        Some(year) => year,
+        None => return Vec::new(),
+    };
+
+    let mut author_years = Vec::new();
+    match last_names.as_slice() {
+        [] => {
+            if let Some(title) = BiblatexUtils::extract_anonymous_title(entry) {
+                author_years.push(format!("_{}_ {}", title, year));
+            }
+        }
+        [author] => author_years.push(format!("{} {}", author, year)),
+        [first, second] => {
+            author_years.push(format!("{} and {} {}", first, second, year));
+            author_years.push(format!("{} {}", first, year));
+        }
+        [first, ..] => {
+            author_years.push(format!("{} et al. {}", first, year));
+            author_years.push(format!("{} {}", first, year));
+        }
+    }
+    author_years
+}
+
+/// The year an entry is cited by, e.g. "2010", or "n.d." for an entry without any date.
+/// Returns `None` if the date of the entry cannot be parsed.
+fn create_citation_year(entry: &Entry) -> Option<String> {
+    match entry.date() {
+        Err(RetrievalError::Missing(_)) => Some(DEFAULT_NO_DATE_TOKEN.to_string()),
+        date => {
+            let date: biblatex::PermissiveType<biblatex::Date> = date.ok()?;
+            let year = BiblatexUtils::extract_year_from_date(&date, entry.key.clone()).ok()?;
+            Some(format!("{:?}", year))
+        }
+    }
+}
+
+/// The outcome of matching a single citation against the bibliography.
+#[derive(Debug)]
+pub struct MatchOutcome {
+    /// The citation without its locator, e.g. "Hegel 2010".
+    pub citation: String,
+    /// The entries matching the citation. More than one means the citation is ambiguous.
+    pub entries: Vec<Entry>,
+}
+
+impl MatchOutcome {
+    /// Whether more than one entry matches the citation.
+    pub fn is_ambiguous(&self) -> bool {
+        self.entries.len() > 1
+    }
+}
+
+/// Matches a single citation, such as "Hegel 2010, 61" or "(see Hegel 2010)",
+/// against the bibliography without needing an MDX file.
+/// A leading signal phrase of the settings is left out of the citation.
+pub fn match_citation(
+    citation: &str,
+    bibliography: &Vec<Entry>,
+    settings: &Settings,
+) -> Result<MatchOutcome, CitationError> {
+    let citation = citation.trim();
+    let citation = citation
+        .strip_prefix('(')
+        .and_then(|c| c.strip_suffix(')'))
+        .unwrap_or(citation);
+    let citation = strip_citation_prefix(citation, &citation_prefixes_for(settings));
+    match_citation_in_index(citation, &build_citation_index(bibliography))
+}
+
+/// Strips the longest leading signal phrase, such as "see also" or "cf.", from a citation,
+/// with any whitespace between its words, and the whitespace following it.
+fn strip_citation_prefix<'a>(citation: &'a str, prefixes: &[String]) -> &'a str {
+    let mut prefixes: Vec<&String> = prefixes.iter().collect();
+    prefixes.sort_by_key(|prefix| std::cmp::Reverse(prefix.len()));
+    prefixes
+        .iter()
+        .find_map(|prefix| {
+            prefix
+                .split_whitespace()
+                .try_fold(citation, |rest, word| rest.trim_start().strip_prefix(word))
+                .filter(|rest| rest.starts_with(char::is_whitespace))
+        })
+        .map_or(citation, str::trim_start)
+}
+
+/// Matches a single citation against a prebuilt index of the bibliography.
+fn match_citation_in_index(
+    citation: &str,
+    citation_index: &HashMap<String, Vec<&Entry>>,
+) -> Result<MatchOutcome, CitationError> {
+    let citation = citation.trim();
+
+    let citations = vec![Citation {
+        text: citation.to_string(),
+        line: 1,
+    }];
+    if verify_citations_format(&citations).is_err() {
+        return Err(CitationError::MalformedCitation(citation.to_string()));
+    }
+    let prepared_citation = create_citations_set(citations).remove(0).text;
+
+    let entries: Vec<Entry> = citation_index
+        .get(&normalize_typography(&prepared_citation))
+        .map(|entries| entries.iter().map(|entry| (*entry).clone()).collect())
+        .unwrap_or_default();
+    if entries.is_empty() {
+        if let Some((year, key, entry_year)) = find_year_mismatch(&prepared_citation, citation_index) {
+            return Err(CitationError::YearMismatch {
+                citation: prepared_citation,
+                year,
+                key,
+                entry_year,
+            });
+        }
+        let suggestion = suggest_citation(&prepared_citation, citation_index);
+        return Err(CitationError::UnmatchedCitation(prepared_citation, suggestion));
+    }
+
+    Ok(MatchOutcome {
+        citation: prepared_citation,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests_typography {
+    use super::*;
+    use biblatex::Bibliography;
+
+    #[test]
+    fn normalize_quotes_dashes_and_spaces() {
+        assert_eq!(normalize_typography("O\u{2019}Neill 2020"), "O'Neill 2020");
+        assert_eq!(normalize_typography("\u{201C}Title\u{201D}"), "\"Title\"");
+        assert_eq!(normalize_typography("Merleau\u{2013}Ponty"), "Merleau-Ponty");
+        assert_eq!(normalize_typography("Hegel\u{00A0}2010"), "Hegel 2010");
+    }
+    #[test]
+    fn curly_apostrophe_matches_straight_apostrophe() {
+        let entries = Bibliography::parse(
+            "@book{oneill2020,
+                title = {Acting on Principle},
+                author = {O'Neill, Onora},
+                year = {2020},
+                publisher = {Cambridge University Press},
+                address = {Cambridge}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec();
+        let citations = citations_from(&["O\u{2019}Neill 2020"]);
+        let matched = match_citations_to_bibliography(&citations, &build_citation_index(&entries)).unwrap();
+        assert_eq!(matched.len(), 1);
+        assert!(match_citation("(O\u{2019}Neill 2020, 12)", &entries, &Settings::default()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests_citation_errors {
+    use super::*;
+
+    fn mock_entries() -> Vec<Entry> {
+        BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries")
+    }
+
+    #[test]
+    fn collects_every_error() {
+        let citations =
+            citations_from(&["Hegel 2010, 61", "Hegel", "Hegel 1807, 12", "Hegel 1807, 14"]);
+        let errors = match_citations_to_bibliography(&citations, &build_citation_index(&mock_entries())).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                (2, CitationError::MalformedCitation("Hegel".to_string())),
+                (3, CitationError::UnmatchedCitation("Hegel 1807".to_string(), None)),
+            ]
+        );
+    }
+    #[test]
+    fn no_errors_for_valid_citations() {
+        let citations = citations_from(&["Hegel 2010, 61", "Kant 1998"]);
+        assert!(match_citations_to_bibliography(&citations, &build_citation_index(&mock_entries())).is_ok());
+    }
+    #[test]
+    fn year_mismatch_is_reported() {
+        let entries = biblatex::Bibliography::parse(
+            "@book{hegel2011,
+                title = {The Science of Logic},
+                author = {Hegel, G.W.F.},
+                year = {2011}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec();
+        let errors = match_citations_to_bibliography(&citations_from(&["Hegel 2010, 61"]), &build_citation_index(&entries)).unwrap_err();
+        let error = CitationError::YearMismatch {
+            citation: "Hegel 2010".to_string(),
+            year: "2010".to_string(),
+            key: "hegel2011".to_string(),
+            entry_year: "2011".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Citation year 2010 does not match entry hegel2011 (2011): (Hegel 2010)"
+        );
+        assert_eq!(errors, vec![(1, error)]);
+    }
+    #[test]
+    fn no_year_mismatch_for_authors_with_several_entries() {
+        let errors = match_citations_to_bibliography(&citations_from(&["Hegel 1807"]), &build_citation_index(&mock_entries())).unwrap_err();
+        assert_eq!(errors, vec![(1, CitationError::UnmatchedCitation("Hegel 1807".to_string(), None))]);
+    }
+}
+
+#[cfg(test)]
+mod tests_locators {
+    use super::*;
+
+    #[test]
+    fn pages_and_ranges_are_valid() {
+        assert!(is_valid_locator("61"));
+        assert!(is_valid_locator("123–45"));
+        assert!(is_valid_locator("123-45"));
+        assert!(is_valid_locator("xii"));
+        assert!(is_valid_locator("514a"));
+        assert!(is_valid_locator("61, 63–64"));
+        assert!(is_valid_locator("61ff."));
+    }
+    #[test]
+    fn volume_and_page_locators_are_valid() {
+        assert!(is_valid_locator("1:61"));
+        assert!(is_valid_locator("2:12–15"));
+        assert!(is_valid_locator("1:61, 2:3"));
+        assert!(!is_valid_locator("1:"));
+        assert!(!is_valid_locator(":61"));
+    }
+    #[test]
+    fn volume_and_page_citations_verify() {
+        let markdown = String::from("Being is nothing (Hegel 2010, 1:61), as Hegel (2010, 2:12–15) adds.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Hegel 2010, 1:61", "Hegel 2010, 2:12–15"]);
+        assert!(verify_citations_format(&citations).is_ok());
+        assert!(collect_locator_errors(&citations).is_empty());
+        let entries = BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries");
+        assert!(match_citations_to_bibliography(&citations, &build_citation_index(&entries)).is_ok());
+    }
+    #[test]
+    fn locator_keywords_are_valid() {
+        assert!(is_valid_locator("chap. 3"));
+        assert!(is_valid_locator("§ 12"));
+        assert!(is_valid_locator("§12"));
+        assert!(is_valid_locator("n. 4"));
+        assert!(is_valid_locator("61n3"));
+        assert!(is_valid_locator("61 n. 3"));
+    }
+    #[test]
+    fn typos_are_invalid() {
+        assert!(!is_valid_locator("abc"));
+        assert!(!is_valid_locator("chap 3x4"));
+        assert!(!is_valid_locator("61, "));
+        assert!(!is_valid_locator("p 61"));
+    }
+    #[test]
+    fn invalid_locators_are_collected() {
+        let citations = citations_from(&["Hegel 2010, 61", "Hegel 2010", "Hegel 2010, abc", "Kant 1998, chap. 3"]);
+        assert_eq!(
+            collect_locator_errors(&citations),
+            vec![(
+                3,
+                CitationError::InvalidLocator("Hegel 2010, abc".to_string(), "abc".to_string())
+            )]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_suggestions {
+    use super::*;
+
+    fn mock_entries() -> Vec<Entry> {
+        BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries")
+    }
+
+    #[test]
+    fn levenshtein_distances() {
+        assert_eq!(levenshtein_distance("Hegel 2010", "Hegel 2010"), 0);
+        assert_eq!(levenshtein_distance("Hegal 2010", "Hegel 2010"), 1);
+        assert_eq!(levenshtein_distance("Hegl 2010", "Hegel 2010"), 1);
+        assert_eq!(levenshtein_distance("", "Kant"), 4);
+    }
+    #[test]
+    fn suggest_closest_citation_for_typo() {
+        let err = match_citation("Hegal 2010, 61", &mock_entries(), &Settings::default()).unwrap_err();
+        assert_eq!(
+            err,
+            CitationError::UnmatchedCitation("Hegal 2010".to_string(), Some("Hegel 2010".to_string()))
+        );
+        assert_eq!(
+            err.to_string(),
+            "Citation not found in the library: (Hegal 2010), did you mean (Hegel 2010)?"
+        );
+    }
+    #[test]
+    fn no_suggestion_beyond_threshold() {
+        let err = match_citation("Fichte 1794", &mock_entries(), &Settings::default()).unwrap_err();
+        assert_eq!(err, CitationError::UnmatchedCitation("Fichte 1794".to_string(), None));
+    }
+    #[test]
+    fn suggestion_in_unmatched_citations_error() {
+        let citations = citations_from(&["Kant 1998", "Hegel 2011"]);
+        let errors = match_citations_to_bibliography(&citations, &build_citation_index(&mock_entries())).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![(
+                2,
+                CitationError::UnmatchedCitation("Hegel 2011".to_string(), Some("Hegel 2010".to_string()))
+            )]
+        );
+        assert_eq!(
+            errors[0].1.to_string(),
+            "Citation not found in the library: (Hegel 2011), did you mean (Hegel 2010)?"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_citation_index {
+    use super::*;
+
+    #[test]
+    fn entries_indexed_by_key_and_author_year() {
+        let entries = BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries");
+        let citation_index = build_citation_index(&entries);
+        assert_eq!(citation_index["Hegel 2010"][0].key, "hegel2010logic");
+        assert_eq!(citation_index["hegel2010logic"][0].key, "hegel2010logic");
+        assert_eq!(citation_index["Hegel 2010"].len(), 1);
+        assert!(citation_index.get("Hegel 1807").is_none());
+    }
+}
+
+#[cfg(test)]
+mod tests_ambiguous_matches {
+    use super::*;
+    use biblatex::Bibliography;
+
+    fn hegel_2010_entries() -> Vec<Entry> {
+        Bibliography::parse(
+            "@book{hegel2010logic,
+                title = {The Science of Logic},
+                author = {Hegel, G.W.F.},
+                year = {2010}
+            }
+            @book{hegel2010encyclopedia,
+                title = {Encyclopedia of the Philosophical Sciences in Basic Outline},
+                author = {Hegel, G.W.F.},
+                year = {2010}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec()
+    }
+
+    #[test]
+    fn citation_matching_multiple_entries() {
+        let errors = match_citations_to_bibliography(&citations_from(&["Hegel 2010"]), &build_citation_index(&hegel_2010_entries()))
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![(
+                1,
+                CitationError::AmbiguousMatch(
+                    "Hegel 2010".to_string(),
+                    vec![
+                        "hegel2010logic".to_string(),
+                        "hegel2010encyclopedia".to_string()
+                    ]
+                )
+            )]
+        );
+    }
+    #[test]
+    fn citation_matching_a_single_entry() {
+        let mut entries = hegel_2010_entries();
+        entries.pop();
+        let matched =
+            match_citations_to_bibliography(&citations_from(&["Hegel 2010"]), &build_citation_index(&entries)).unwrap();
+        assert_eq!(matched.len(), 1);
+    }
+}
+
+#[cfg(test)]
+mod tests_authorless_entries {
+    use super::*;
+    use biblatex::Bibliography;
+
+    #[test]
+    fn authorless_book_cited_by_editor() {
+        let entries = Bibliography::parse(
+            "@book{beiser2008companion,
+                title = {The Cambridge Companion to Hegel},
+                editor = {Beiser, Frederick},
+                year = {2008}
+            }
+            @book{pamphlet2020,
+                title = {A Pamphlet},
+                year = {2020}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec();
+        let matched =
+            match_citations_to_bibliography(&citations_from(&["Beiser 2008"]), &build_citation_index(&entries)).unwrap();
+        assert_eq!(matched[0].key, "beiser2008companion");
+        let matched =
+            match_citations_to_bibliography(&citations_from(&["_A Pamphlet_ 2020"]), &build_citation_index(&entries)).unwrap();
+        assert_eq!(matched[0].key, "pamphlet2020");
+        assert!(match_citations_to_bibliography(&citations_from(&["Doe 2020"]), &build_citation_index(&entries)).is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_allowed_keys {
+    use super::*;
+    use biblatex::Bibliography;
+
+    #[test]
+    fn disallowed_keys_are_reported() {
+        let entries = Bibliography::parse(
+            "@book{hegel2010logic, title = {The Science of Logic}, author = {Hegel, G.W.F.}, year = {2010}}
+            @book{doe2020blog, title = {A Blog Post}, author = {Doe, Jane}, year = {2020}}",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec();
+        let allowed_keys = vec!["hegel2010logic".to_string()];
+        assert_eq!(find_disallowed_keys(&entries, &allowed_keys), vec!["doe2020blog"]);
+
+        let allowed_keys = vec!["hegel2010logic".to_string(), "doe2020blog".to_string()];
+        assert!(find_disallowed_keys(&entries, &allowed_keys).is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests_unused_keys {
+    use super::*;
+
+    fn mock_entries() -> Vec<Entry> {
+        BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries")
+    }
+    fn verify(settings: &Settings) -> Result<VerifyReport, Error> {
+        let mdx_paths = vec!["tests/mocks/data/plain-markdown.md".to_string()];
+        verify_mdx_files(mdx_paths, &mock_entries(), settings)
+    }
+
+    #[test]
+    fn unused_keys_exclude_cited_entries() {
+        let verify_report = verify(&Settings::default()).expect("Failed to verify");
+        let unused_keys = find_unused_keys(&mock_entries(), &verify_report.articles);
+        assert!(!unused_keys.contains(&"hegel2010logic".to_string()));
+        assert_eq!(unused_keys.len(), mock_entries().len() - 1);
+    }
+    #[test]
+    fn no_unused_keys_when_everything_is_cited() {
+        let verify_report = verify(&Settings::default()).expect("Failed to verify");
+        let cited_entries = verify_report.articles[0].matched_citations.clone();
+        assert!(find_unused_keys(&cited_entries, &verify_report.articles).is_empty());
+    }
+    #[test]
+    fn warn_unused_passes_and_strict_unused_fails() {
+        let settings = Settings {
+            warn_unused: true,
+            ..Default::default()
+        };
+        assert!(verify(&settings).is_ok());
+        let settings = Settings {
+            strict_unused: true,
+            ..Default::default()
+        };
+        let err = verify(&settings).expect_err("Unused entries should fail verification");
+        assert!(err.to_string().starts_with("Bibliography entries never cited: "));
+    }
+}
+
+#[cfg(test)]
+mod tests_index_title {
+    use super::*;
+
+    #[test]
+    fn identical_titles() {
+        assert!(!titles_differ_trivially("Science of Logic", "Science of Logic"));
+    }
+    #[test]
+    fn titles_differing_by_case_or_whitespace() {
+        assert!(titles_differ_trivially("Science of Logic", "Science of logic"));
+        assert!(titles_differ_trivially("Science of Logic", "Science  of Logic "));
+    }
+    #[test]
+    fn intentionally_different_titles() {
+        assert!(!titles_differ_trivially("Science of Logic", "Logic, Science of"));
+    }
+    #[test]
+    fn derived_index_title_is_trimmed() {
+        assert_eq!(derive_index_title(" Science  of\tLogic "), "Science of Logic");
+    }
+    #[test]
+    fn index_title_derived_only_when_enabled() {
+        let mdx_path = "tests/mocks/data/first-paragraph.mdx".to_string();
+        let entries = BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries");
+        let index_title = |settings: &Settings| match verify_mdx_file(
+            &mdx_path,
+            &entries,
+            &build_citation_index(&entries),
+            settings,
+            None,
+            &default_citation_regexes(),
+        ) {
+            Ok(FileVerification::Article(article, _)) => article.metadata.index_title,
+            _ => panic!("Failed to verify {}", mdx_path),
+        };
+
+        assert_eq!(index_title(&Settings::default()), None);
+        let settings = Settings {
+            derive_index_title: true,
+            ..Default::default()
+        };
+        assert_eq!(index_title(&settings), Some("First Paragraph".to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests_match_citation {
+    use super::*;
+
+    fn mock_entries() -> Vec<Entry> {
+        BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries")
+    }
+
+    #[test]
+    fn matching_citation() {
+        let outcome = match_citation("Hegel 2010, 61", &mock_entries(), &Settings::default()).unwrap();
+        assert_eq!(outcome.citation, "Hegel 2010");
+        assert_eq!(outcome.entries.len(), 1);
+        assert_eq!(outcome.entries[0].key, "hegel2010logic");
+        assert!(!outcome.is_ambiguous());
+    }
+    #[test]
+    fn matching_citation_with_parentheses_and_see() {
+        let outcome = match_citation("(see Kant 1998)", &mock_entries(), &Settings::default()).unwrap();
+        assert_eq!(outcome.entries[0].key, "kant1998critique");
+    }
+    #[test]
+    fn matching_citation_with_configured_prefix() {
+        let settings = Settings {
+            citation_prefixes: Some(vec!["compare".to_string()]),
+            ..Default::default()
+        };
+        let outcome = match_citation("(compare Kant 1998)", &mock_entries(), &settings).unwrap();
+        assert_eq!(outcome.entries[0].key, "kant1998critique");
+    }
+    #[test]
+    fn malformed_citation() {
+        let err = match_citation("Hegel", &mock_entries(), &Settings::default()).unwrap_err();
+        assert_eq!(err, CitationError::MalformedCitation("Hegel".to_string()));
+    }
+    #[test]
+    fn unmatched_citation() {
+        let err = match_citation("Hegel 1807", &mock_entries(), &Settings::default()).unwrap_err();
+        assert_eq!(err, CitationError::UnmatchedCitation("Hegel 1807".to_string(), None));
+    }
+}
+
+#[cfg(test)]
+mod tests_read_mdx_file {
+    use super::*;
+
+    #[test]
+    fn frontmatter_and_markdown() {
+        let content = "---\ntitle: Test\n---\n\n# Heading\n";
+        let (metadata, markdown) = split_frontmatter(content).unwrap();
+        assert_eq!(metadata, "title: Test\n");
+        assert_eq!(markdown, "\n\n# Heading\n");
+    }
+    #[test]
+    fn thematic_breaks_in_markdown() {
+        let content = "---\ntitle: Test\n---\n\nFirst part.\n\n---\n\nSecond part.\n\n---\n\nThird part.\n";
+        let (metadata, markdown) = split_frontmatter(content).unwrap();
+        assert_eq!(metadata, "title: Test\n");
+        assert_eq!(
+            markdown,
+            "\n\nFirst part.\n\n---\n\nSecond part.\n\n---\n\nThird part.\n"
+        );
+    }
+    #[test]
+    fn thematic_break_right_after_frontmatter() {
+        let content = "---\ntitle: Test\n---\n---\nText with -- and --- inline.\n";
+        let (metadata, markdown) = split_frontmatter(content).unwrap();
+        assert_eq!(metadata, "title: Test\n");
+        assert_eq!(markdown, "\n---\nText with -- and --- inline.\n");
+    }
+    #[test]
+    fn missing_frontmatter() {
+        assert!(split_frontmatter("# Heading\n---\n").is_none());
+        assert!(split_frontmatter("---\ntitle: Unclosed\n").is_none());
+        assert!(split_frontmatter("+++\ntitle = \"Mismatched\"\n---\n").is_none());
+    }
+    #[test]
+    fn toml_frontmatter_and_markdown() {
+        let content = "+++\ntitle = \"Test\"\n+++\n\n---\n# Heading\n";
+        let (metadata, markdown) = split_frontmatter(content).unwrap();
+        assert_eq!(metadata, "title = \"Test\"\n");
+        assert_eq!(markdown, "\n\n---\n# Heading\n");
+    }
+    #[test]
+    fn toml_frontmatter_file() {
+        let (metadata, markdown, _) = read_mdx_file("tests/mocks/data/toml-frontmatter.md", &Settings::default()).unwrap();
+        assert_eq!(metadata.title, "TOML Frontmatter");
+        assert!(metadata.is_article);
+        assert_eq!(metadata.index_title, Some("Frontmatter, TOML".to_string()));
+        assert_eq!(metadata.tags, vec!["logic", "hugo"]);
+        assert!(markdown.contains("(Hegel 2010, 61)"));
+    }
+    #[test]
+    fn block_scalar_description_with_dashes() {
+        let (metadata, markdown, _) =
+            read_mdx_file("tests/mocks/data/block-description.mdx", &Settings::default()).unwrap();
+        assert_eq!(metadata.title, "Block Description");
+        assert!(metadata.description.contains("\n---\n"));
+        assert!(metadata.description.starts_with("Learn about the beginning"));
+        assert!(markdown.contains("# MDX Test Sample"));
+    }
+}
+
+#[cfg(test)]
+mod tests_frontmatter_people {
+    use super::*;
+
+    fn parse_metadata(frontmatter: &str) -> Metadata {
+        serde_yaml::from_str(frontmatter).expect("Failed to parse frontmatter")
+    }
+
+    #[test]
+    fn authors_as_string() {
+        let metadata = parse_metadata(
+            "title: Test\ndescription: Test\nisArticle: true\nauthors: Filip Niklas (2024)\n",
+        );
+        assert_eq!(metadata.authors, Some("Filip Niklas (2024)".to_string()));
+        assert_eq!(metadata.editors, None);
+    }
+    #[test]
+    fn authors_as_list_of_names() {
+        let metadata = parse_metadata(
+            "title: Test\ndescription: Test\nisArticle: true\nauthors:\n  - Filip Niklas (2024)\n  - Jane Doe (2025)\n",
+        );
+        assert_eq!(
+            metadata.authors,
+            Some("Filip Niklas (2024)  \nJane Doe (2025)".to_string())
+        );
+    }
+    #[test]
+    fn people_as_list_of_objects() {
+        let metadata = parse_metadata(
+            "title: Test\ndescription: Test\nisArticle: true\neditors:\n  - name: Jane Doe\n    url: https://example.org\n  - name: John Roe\n",
+        );
+        assert_eq!(
+            metadata.editors,
+            Some("[Jane Doe](https://example.org)  \nJohn Roe".to_string())
+        );
+    }
+    #[test]
+    fn authors_as_toml_list() {
+        let metadata: Metadata = toml::from_str(
+            "title = \"Test\"\ndescription = \"Test\"\nisArticle = true\nauthors = [\"Jane Doe\", { name = \"John Roe\", url = \"https://example.org\" }]\n",
+        )
+        .expect("Failed to parse frontmatter");
+        assert_eq!(
+            metadata.authors,
+            Some("Jane Doe  \n[John Roe](https://example.org)".to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_custom_metadata {
+    use super::*;
+
+    const FRONTMATTER: &str = "title: Test\ndescription: Test\nisArticle: true\ntags: [logic]\nslug: test-article\nlastUpdated: 2024-05-01\nseries:\n  name: Logic\n  part: 2\n";
+
+    fn extra_value<'a>(metadata: &'a Metadata, key: &str) -> Option<&'a serde_yaml::Value> {
+        metadata.extra.get(&serde_yaml::Value::from(key))
+    }
+
+    #[test]
+    fn unknown_keys_are_kept() {
+        let metadata: Metadata = serde_yaml::from_str(FRONTMATTER).unwrap();
+        assert_eq!(metadata.tags, vec!["logic"]);
+        assert_eq!(metadata.extra.len(), 3);
+        assert_eq!(extra_value(&metadata, "slug").and_then(|value| value.as_str()), Some("test-article"));
+        assert_eq!(
+            extra_value(&metadata, "lastUpdated").and_then(|value| value.as_str()),
+            Some("2024-05-01")
+        );
+        assert_eq!(extra_value(&metadata, "series").unwrap()["part"].as_u64(), Some(2));
+        assert!(extra_value(&metadata, "title").is_none());
+    }
+    #[test]
+    fn unknown_keys_round_trip() {
+        let metadata: Metadata = serde_yaml::from_str(FRONTMATTER).unwrap();
+        let serialized = serde_yaml::to_string(&metadata).unwrap();
+        let reparsed: Metadata = serde_yaml::from_str(&serialized).unwrap();
+        assert_eq!(reparsed.extra, metadata.extra);
+    }
+    #[test]
+    fn unknown_toml_keys_are_kept() {
+        let metadata: Metadata = toml::from_str(
+            "title = \"Test\"\ndescription = \"Test\"\nisArticle = true\nslug = \"test-article\"\n",
+        )
+        .unwrap();
+        assert_eq!(extra_value(&metadata, "slug").and_then(|value| value.as_str()), Some("test-article"));
+    }
+    #[test]
+    fn no_unknown_keys() {
+        let metadata: Metadata =
+            serde_yaml::from_str("title: Test\ndescription: Test\nisArticle: true\n").unwrap();
+        assert!(metadata.extra.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests_required_metadata {
+    use super::*;
+
+    fn deserialize_error(frontmatter: &str, settings: &Settings) -> String {
+        deserialize_metadata(frontmatter, false, "a.mdx", settings)
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn missing_title() {
+        let err = deserialize_error("description: Test\nisArticle: true\n", &Settings::default());
+        assert_eq!(err, "Missing required frontmatter field `title` in a.mdx");
+    }
+    #[test]
+    fn missing_description() {
+        let err = deserialize_error("title: Test\nisArticle: true\n", &Settings::default());
+        assert_eq!(err, "Missing required frontmatter field `description` in a.mdx");
+    }
+    #[test]
+    fn missing_fields_are_all_reported() {
+        let err = deserialize_error("title:\nauthors: Jane Doe\n", &Settings::default());
+        assert_eq!(
+            err,
+            "Missing required frontmatter field `title` in a.mdx\nMissing required frontmatter field `description` in a.mdx\nMissing required frontmatter field `isArticle` in a.mdx"
+        );
+        let err = deserialize_metadata(
+            "title = \"Test\"\nisArticle = true\n",
+            true,
+            "a.md",
+            &Settings::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Missing required frontmatter field `description` in a.md");
+    }
+    #[test]
+    fn configured_required_fields() {
+        let settings = Settings {
+            required_metadata: Some(vec!["title".to_string(), "authors".to_string()]),
+            ..Default::default()
+        };
+        let err = deserialize_error("title: Test\ndescription: Test\nisArticle: true\n", &settings);
+        assert_eq!(err, "Missing required frontmatter field `authors` in a.mdx");
+        let metadata =
+            deserialize_metadata("title: Test\nauthors: Jane Doe\n", false, "a.mdx", &settings).unwrap();
+        assert_eq!(metadata.description, "");
+        assert!(!metadata.is_article);
+    }
+    #[test]
+    fn invalid_field_is_reported_by_serde() {
+        let err = deserialize_error(
+            "title: Test\ndescription: Test\nisArticle: maybe\n",
+            &Settings::default(),
+        );
+        assert!(err.ends_with(" in a.mdx"));
+        assert!(!err.starts_with("Missing required frontmatter field"));
+    }
+}
+
+#[cfg(test)]
+mod tests_balanced_parentheses {
+    use super::*;
+
+    #[test]
+    fn balanced_parentheses() {
+        let markdown = String::from("This is a balanced citation (Spinoza 2021).");
+        assert_eq!(check_parentheses_balance(&markdown), Ok(()));
+    }
+    #[test]
+    fn unbalanced_parentheses_more_open() {
+        let markdown = String::from("This is an unbalanced citation (Spinoza 2021.");
+        assert_eq!(check_parentheses_balance(&markdown), Err((1, 32)));
+    }
+    #[test]
+    fn unbalanced_parentheses_more_close() {
+        let markdown = String::from("This is an unbalanced citation Spinoza 2021).");
+        assert_eq!(check_parentheses_balance(&markdown), Err((1, 44)));
+    }
+    #[test]
+    fn unbalanced_parentheses_more_open_on_a_later_line() {
+        let markdown = String::from("A citation (Hegel 2010).\n\nAn (unclosed (Kant 1998) one.");
+        assert_eq!(check_parentheses_balance(&markdown), Err((3, 4)));
+    }
+    #[test]
+    fn unbalanced_parentheses_more_close_on_a_later_line() {
+        let markdown = String::from("A citation (Hegel 2010).\n\nA stray) one (Kant 1998).");
+        assert_eq!(check_parentheses_balance(&markdown), Err((3, 8)));
+    }
+}
+
+#[cfg(test)]
+mod tests_balanced_parentheses_per_paragraph {
+    use super::*;
+
+    #[test]
+    fn balanced_paragraphs() {
+        let markdown = String::from("A citation (Spinoza 2021).\n\nAnother (Kant 1998,\n23).");
+        assert_eq!(check_parentheses_balance_per_paragraph(&markdown), Ok(()));
+    }
+    #[test]
+    fn separate_errors_do_not_cancel_out() {
+        let markdown = String::from(
+            "First paragraph (Hegel 2020.\n\
+            \n\
+            Second paragraph with a stray ).",
+        );
+        assert_eq!(check_parentheses_balance(&markdown), Ok(()));
+        assert_eq!(check_parentheses_balance_per_paragraph(&markdown), Err(1));
+    }
+    #[test]
+    fn stray_closing_parenthesis() {
+        let markdown = String::from("Fine (Hegel 2020).\n\nStray Hegel 2020).");
+        assert_eq!(check_parentheses_balance_per_paragraph(&markdown), Err(3));
+    }
+    #[test]
+    fn unclosed_parenthesis_at_end() {
+        let markdown = String::from("Fine.\n\nOpen (Hegel 2020.\nStill open.");
+        assert_eq!(check_parentheses_balance_per_paragraph(&markdown), Err(3));
+    }
+}
+
+#[cfg(test)]
+mod tests_inline_code {
+    use super::*;
+
+    #[test]
+    fn citation_inside_inline_code_is_skipped() {
+        let markdown = String::from("Write `see (Hegel 2010)` to cite, as in (Kant 1998).");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Kant 1998"]);
+    }
+    #[test]
+    fn multiple_inline_code_spans_on_one_line() {
+        let markdown =
+            String::from("Both `(Hegel 2010)` and `(Hegel 1991)` are cited (Kant 1998), not ``(Hegel 2018)``.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Kant 1998"]);
+    }
+    #[test]
+    fn escaped_backticks_do_not_open_inline_code() {
+        let markdown = String::from("A \\` backtick (Hegel 2010) and another \\` (Kant 1998).");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Hegel 2010", "Kant 1998"]);
+    }
+    #[test]
+    fn mask_inline_code_spans() {
+        assert_eq!(mask_inline_code("a `(b)` c"), "a       c");
+        assert_eq!(mask_inline_code("a ``x ` y`` c"), "a           c");
+        assert_eq!(mask_inline_code("unclosed `(Hegel 2010)"), "unclosed `(Hegel 2010)");
+        assert_eq!(mask_inline_code("mismatched ``(Hegel 2010)`"), "mismatched ``(Hegel 2010)`");
+    }
 }
 
 #[cfg(test)]
@@ -324,10 +2250,84 @@ mod tests_citation_extraction {
         assert_eq!(citations, vec!["Spinoza 2021", "Kant 2020, 123"]);
     }
     #[test]
+    fn single_citation_prefixed_see_also() {
+        let markdown = String::from("This is a citation (see also Marx 1867) in the text.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Marx 1867"]);
+    }
+    #[test]
+    fn single_citation_prefixed_cf() {
+        let markdown = String::from("This is a citation (cf. Hegel 2010) in the text.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Hegel 2010"]);
+    }
+    #[test]
+    fn single_citation_prefixed_eg() {
+        let markdown = String::from("This is a citation (e.g., Kant 2020, 12) in the text.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Kant 2020, 12"]);
+    }
+    #[test]
+    fn configured_citation_prefixes() {
+        let settings = Settings {
+            citation_prefixes: Some(vec!["compare".to_string(), "but  see".to_string()]),
+            ..Default::default()
+        };
+        let citation_regexes = citation_regexes_for(&settings);
+        let citation_regexes: Vec<&Regex> = citation_regexes.iter().map(AsRef::as_ref).collect();
+        let markdown = String::from("Citations (compare Hegel 2010), (but see Kant 2020) and (cf. Marx 1867).");
+        let citations = extract_citations_with_regexes(&markdown, &citation_regexes);
+        assert_eq!(citations, vec!["Hegel 2010", "Kant 2020"]);
+    }
+    #[test]
+    fn prefixes_are_stripped_before_matching() {
+        let prefixes = citation_prefixes_for(&Settings::default());
+        assert_eq!(strip_citation_prefix("see also Marx 1867", &prefixes), "Marx 1867");
+        assert_eq!(strip_citation_prefix("e.g., Kant 2020, 12", &prefixes), "Kant 2020, 12");
+        assert_eq!(strip_citation_prefix("Seebohm 2020", &prefixes), "Seebohm 2020");
+        let prefixes = vec!["but see".to_string()];
+        assert_eq!(strip_citation_prefix("but  see Marx 1867", &prefixes), "Marx 1867");
+        assert_eq!(strip_citation_prefix("see Marx 1867", &prefixes), "see Marx 1867");
+    }
+    #[test]
+    fn citation_with_trailing_period() {
+        let markdown = String::from("A citation with a stray period (Hegel 2020.) in the text.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Hegel 2020"]);
+        assert!(verify_citations_format(&citations).is_ok());
+    }
+    #[test]
+    fn citation_with_trailing_comma() {
+        let markdown = String::from("A citation with a stray comma (Hegel 2020,) in the text.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Hegel 2020"]);
+        assert!(verify_citations_format(&citations).is_ok());
+    }
+    #[test]
+    fn citation_with_page_and_trailing_period() {
+        let markdown = String::from("A citation (Hegel 2020, 61.) in the text.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Hegel 2020, 61"]);
+    }
+    #[test]
+    fn anonymous_work_citation() {
+        let markdown =
+            String::from("An anonymous work (_Beowulf_ 2000, 3) and (see _The Song of Roland_ 1990).");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["_Beowulf_ 2000, 3", "_The Song of Roland_ 1990"]);
+        assert!(verify_citations_format(&citations).is_ok());
+    }
+    #[test]
+    fn italic_text_without_year_is_not_a_citation() {
+        let markdown = String::from("A German term (_Befreiung_) and a title (_Logic_, 23).");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert!(citations.is_empty());
+    }
+    #[test]
     fn no_citation() {
         let markdown = String::from("This text has no citations.");
         let citations = extract_citations_from_markdown(&markdown);
-        assert_eq!(citations, Vec::<String>::new());
+        assert!(citations.is_empty());
     }
     #[test]
     fn citation_with_additional_text() {
@@ -344,6 +2344,8 @@ mod tests_citation_extraction {
         );
         let citations = extract_citations_from_markdown(&markdown);
         assert_eq!(citations, vec!["Aristotle 2020", "Hume 2018"]);
+        assert_eq!(citations[0].line, 1);
+        assert_eq!(citations[1].line, 2);
     }
     #[test]
     fn incomplete_citation_opening_parenthesis_only() {
@@ -366,50 +2368,438 @@ mod tests_citation_extraction {
     }
 }
 
+#[cfg(test)]
+mod tests_narrative_citations {
+    use super::*;
+
+    fn mock_entries() -> Vec<Entry> {
+        BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries")
+    }
+
+    #[test]
+    fn narrative_citation() {
+        let markdown = String::from("As Hegel (2010) notes, being is nothing.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Hegel 2010"]);
+    }
+    #[test]
+    fn narrative_citation_with_pages() {
+        let markdown = String::from("As Hegel (2010, 61) notes, being is nothing.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Hegel 2010, 61"]);
+    }
+    #[test]
+    fn narrative_citations_mixed_with_parenthetical_ones() {
+        let markdown = String::from(
+            "As Hegel (2010, 61) notes, being is nothing (Houlgate 2022, 5).\nMcTaggart (1910) disagrees (see Burbidge 1981).",
+        );
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(
+            citations,
+            vec!["Hegel 2010, 61", "Houlgate 2022, 5", "McTaggart 1910", "Burbidge 1981"]
+        );
+        assert_eq!(citations[2].line, 2);
+    }
+    #[test]
+    fn life_dates_are_not_citations() {
+        let markdown = String::from("Hegel (1770–1831) was a German philosopher.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert!(citations.is_empty());
+    }
+    #[test]
+    fn narrative_citations_are_matched_to_the_bibliography() {
+        let markdown = String::from("As Hegel (2010, 61) notes, and Hegal (2010) does not.");
+        let citations = extract_citations_from_markdown(&markdown);
+        let errors = match_citations_to_bibliography(&citations, &build_citation_index(&mock_entries())).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![(
+                1,
+                CitationError::UnmatchedCitation("Hegal 2010".to_string(), Some("Hegel 2010".to_string()))
+            )]
+        );
+    }
+    #[test]
+    fn injected_authors_are_not_cited() {
+        let content = "Some text (Hegel 2010).\n\n## Bibliography\n\n<div className=\"text-sm\">\n- Entry.\n</div>\n\n**Authors**  \nJane Doe (2024)\n";
+        let citations = extract_citations_from_markdown(&inserters::strip_injected_content(content, &Settings::default()));
+        assert_eq!(citations, vec!["Hegel 2010"]);
+    }
+}
+
+#[cfg(test)]
+mod tests_citation_links {
+    use super::*;
+
+    fn mock_entries() -> Vec<Entry> {
+        BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries")
+    }
+    fn link_citations(content: &str) -> String {
+        let entries = mock_entries();
+        link_citations_in_mdx(content, &build_citation_index(&entries), &entries, &Settings::default())
+    }
+
+    #[test]
+    fn parenthetical_and_narrative_citations_are_linked() {
+        let content = "---\ntitle: (Hegel 2010)\n---\nBeing is nothing (Hegel 2010, 61).\nAs Houlgate (2022) notes.\n";
+        assert_eq!(
+            link_citations(content),
+            "---\ntitle: (Hegel 2010)\n---\nBeing is nothing [(Hegel 2010, 61)](#cite-hegel2010logic).\n\
+            As [Houlgate (2022)](#cite-houlgate2022hegel) notes.\n"
+        );
+    }
+    #[test]
+    fn linking_twice_changes_nothing() {
+        let content = "Being is nothing (see Hegel 2010).\r\n";
+        let linked_content = link_citations(content);
+        assert_eq!(linked_content, "Being is nothing [(see Hegel 2010)](#cite-hegel2010logic).\r\n");
+        assert_eq!(link_citations(&linked_content), linked_content);
+        assert_eq!(extract_citations_from_markdown(&linked_content), vec!["Hegel 2010"]);
+    }
+    #[test]
+    fn only_linked_entries_are_linked() {
+        let entries = mock_entries();
+        let linked_entries: Vec<Entry> =
+            entries.iter().filter(|entry| entry.key == "hegel2010logic").cloned().collect();
+        assert_eq!(
+            link_citations_in_mdx("(Hegel 2010) and (Kant 1998).", &build_citation_index(&entries), &linked_entries, &Settings::default()),
+            "[(Hegel 2010)](#cite-hegel2010logic) and (Kant 1998)."
+        );
+    }
+    #[test]
+    fn code_and_unmatched_citations_are_not_linked() {
+        let content = "In `é (Hegel 2010)` and (Doe 2020), but (Kant 1998).";
+        assert_eq!(
+            link_citations(content),
+            "In `é (Hegel 2010)` and (Doe 2020), but [(Kant 1998)](#cite-kant1998critique)."
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_no_date {
+    use super::*;
+    use biblatex::Bibliography;
+
+    fn undated_entries() -> Vec<Entry> {
+        Bibliography::parse(
+            "@book{hegel_undated,
+                title = {Fragments},
+                author = {Hegel, G.W.F.},
+                publisher = {Meiner},
+                address = {Hamburg}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec()
+    }
+    fn mock_entries() -> Vec<Entry> {
+        BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries")
+    }
+
+    #[test]
+    fn no_date_citations_are_extracted() {
+        let markdown = String::from("Undated (Hegel n.d.), with pages (see Hegel n.d., 12) and Hegel (n.d.) too.");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Hegel n.d.", "Hegel n.d., 12", "Hegel n.d."]);
+        assert!(verify_citations_format(&citations).is_ok());
+    }
+    #[test]
+    fn no_date_citation_matches_undated_entry() {
+        let matched = match_citations_to_bibliography(&citations_from(&["Hegel n.d."]), &build_citation_index(&undated_entries())).unwrap();
+        assert_eq!(matched[0].key, "hegel_undated");
+        assert!(match_citations_to_bibliography(&citations_from(&["Hegel n.d."]), &build_citation_index(&mock_entries())).is_err());
+    }
+    #[test]
+    fn configured_no_date_token() {
+        let settings = Settings {
+            no_date_token: Some("o.J.".to_string()),
+            ..Default::default()
+        };
+        let citation_regexes = citation_regexes_for(&settings);
+        let citation_regexes: Vec<&Regex> = citation_regexes.iter().map(AsRef::as_ref).collect();
+        let markdown = String::from("Undated (Hegel o.J., 12).");
+        let citations = extract_citations_with_regexes(&markdown, &citation_regexes);
+        assert_eq!(citations, vec!["Hegel o.J., 12"]);
+        assert_eq!(normalize_no_date_token(&citations[0].text, "o.J."), "Hegel n.d., 12");
+    }
+}
+
+#[cfg(test)]
+mod tests_multiple_authors {
+    use super::*;
+    use biblatex::Bibliography;
+
+    fn multi_author_entries() -> Vec<Entry> {
+        Bibliography::parse(
+            "@book{marx1848manifesto,
+                title = {The Communist Manifesto},
+                author = {Marx, Karl and Engels, Friedrich},
+                year = {1848}
+            }
+            @book{doe2020logic,
+                title = {Logic for Everyone},
+                author = {Doe, Jane and Roe, Richard and Poe, Edgar},
+                year = {2020}
+            }
+            @book{roe2021dialectic,
+                title = {Dialectic},
+                editor = {Roe, Richard and Doe, Jane},
+                year = {2021}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec()
+    }
+
+    #[test]
+    fn multiple_author_citations_are_extracted() {
+        let markdown = String::from(
+            "As Marx and Engels (1848, 5) hold (see Doe et al. 2020), as do Doe et al. (2020).",
+        );
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Marx and Engels 1848, 5", "Doe et al. 2020", "Doe et al. 2020"]);
+    }
+    #[test]
+    fn two_author_citation_matches() {
+        let matched = match_citations_to_bibliography(
+            &citations_from(&["Marx and Engels 1848", "Roe and Doe 2021"]),
+            &build_citation_index(&multi_author_entries()),
+        )
+        .unwrap();
+        let keys: Vec<&str> = matched.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, vec!["marx1848manifesto", "roe2021dialectic"]);
+    }
+    #[test]
+    fn three_author_citation_matches_et_al() {
+        let matched =
+            match_citations_to_bibliography(&citations_from(&["Doe et al. 2020"]), &build_citation_index(&multi_author_entries()))
+                .unwrap();
+        assert_eq!(matched[0].key, "doe2020logic");
+        assert!(
+            match_citations_to_bibliography(&citations_from(&["Doe and Roe 2020"]), &build_citation_index(&multi_author_entries()))
+                .is_err()
+        );
+    }
+    #[test]
+    fn first_author_alone_still_matches() {
+        let matched =
+            match_citations_to_bibliography(&citations_from(&["Marx 1848"]), &build_citation_index(&multi_author_entries())).unwrap();
+        assert_eq!(matched[0].key, "marx1848manifesto");
+    }
+}
+
+#[cfg(test)]
+mod tests_citation_regex_override {
+    use super::*;
+
+    #[test]
+    fn named_citation_group() {
+        let citation_regex = Regex::new(r"\[(?P<citation>[A-Z][^\]]*? \d{4}(?:, [^\]]*)?)\]").unwrap();
+        let markdown = String::from("House style uses brackets [Hegel 2010, 61] and [Kant 1998].");
+        let citations = extract_citations_with_regex(&markdown, &citation_regex);
+        assert_eq!(citations, vec!["Hegel 2010, 61", "Kant 1998"]);
+    }
+    #[test]
+    fn last_capture_group() {
+        let citation_regex = Regex::new(r"\((cf\.\s)?([A-Z][^()]*? \d{4})\)").unwrap();
+        let markdown = String::from("Compare (cf. Hegel 2010) with (Kant 1998).");
+        let citations = extract_citations_with_regex(&markdown, &citation_regex);
+        assert_eq!(citations, vec!["Hegel 2010", "Kant 1998"]);
+    }
+}
+
+#[cfg(test)]
+mod tests_citation_density {
+    use super::*;
+
+    #[test]
+    fn paragraph_within_threshold() {
+        let markdown = String::from("One (Hegel 2010) and two (Kant 1998).\n");
+        assert!(check_citation_density(&markdown, 2).is_empty());
+    }
+    #[test]
+    fn paragraph_over_threshold() {
+        let markdown = String::from(
+            "Intro without citations.\n\
+            \n\
+            First (Hegel 2010) and\n\
+            second (Kant 1998) and third (Hume 2018).\n\
+            \n\
+            Last (Hegel 2010).",
+        );
+        assert_eq!(check_citation_density(&markdown, 2), vec![(3, 4, 3)]);
+    }
+    #[test]
+    fn multiple_paragraphs_over_threshold() {
+        let markdown = String::from(
+            "(Hegel 2010) (Kant 1998)\n\
+            \n\
+            (Hume 2018) (Plato 2019)",
+        );
+        assert_eq!(
+            check_citation_density(&markdown, 1),
+            vec![(1, 1, 2), (3, 3, 2)]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_classical_citations {
+    use super::*;
+    use biblatex::Bibliography;
+
+    fn classical_entries() -> Vec<Entry> {
+        Bibliography::parse(
+            "@book{plato1997republic,
+                title = {Republic},
+                author = {Plato},
+                entrysubtype = {classical},
+                publisher = {Hackett},
+                address = {Indianapolis}
+            }
+            @book{aristotle1984ethics,
+                title = {Nicomachean Ethics},
+                shorttitle = {Ethics},
+                author = {Aristotle},
+                entrysubtype = {classical},
+                year = {1984},
+                publisher = {Princeton University Press},
+                address = {Princeton}
+            }",
+        )
+        .expect("Failed to parse bibliography")
+        .into_vec()
+    }
+
+    #[test]
+    fn extract_classical_citations() {
+        let markdown = String::from(
+            "The cave (Plato, Republic 514a–517c) and the good (see Aristotle 1094a1), \
+            but not (Hegel 2010, 61).",
+        );
+        let citations = extract_classical_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Plato, Republic 514a–517c", "Aristotle 1094a1"]);
+    }
+    #[test]
+    fn classical_citations_are_told_apart_from_author_year() {
+        assert!(is_classical_citation("Aristotle 1094a1"));
+        assert!(is_classical_citation("Plato, Republic 514a"));
+        assert!(!is_classical_citation("Hegel 2010"));
+        assert!(!is_classical_citation("Hegel 2010, 61"));
+    }
+    #[test]
+    fn split_classical_citations() {
+        assert_eq!(
+            split_classical_citation("Plato, Republic 514a"),
+            ("Plato", Some("Republic"), "514a")
+        );
+        assert_eq!(
+            split_classical_citation("Aristotle 1094a1"),
+            ("Aristotle", None, "1094a1")
+        );
+    }
+    #[test]
+    fn match_classical_citations() {
+        let citations = vec![
+            "Plato, Republic 514a".to_string(),
+            "Aristotle, Ethics 1094a1".to_string(),
+            "Aristotle 1095b".to_string(),
+        ];
+        let matched = match_classical_citations_to_bibliography(citations, &classical_entries())
+            .unwrap();
+        let keys: Vec<&str> = matched.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, vec!["plato1997republic", "aristotle1984ethics"]);
+    }
+    #[test]
+    fn unmatched_classical_citation() {
+        let citations = vec!["Plato, Laws 624a".to_string()];
+        assert!(match_classical_citations_to_bibliography(citations, &classical_entries()).is_err());
+    }
+}
+
 #[cfg(test)]
 mod tests_validate_citations {
     use super::*;
 
     #[test]
     fn valid_citations() {
-        let citations = vec!["Hegel 2021".to_string(), "Kant 2020, 123".to_string()];
+        let citations = citations_from(&["Hegel 2021", "Kant 2020, 123"]);
         assert!(verify_citations_format(&citations).is_ok());
     }
     #[test]
     fn missing_year() {
-        let citations = vec!["Hegel".to_string(), "Kant 2020, 123".to_string()];
+        let citations = citations_from(&["Hegel", "Kant 2020, 123"]);
         assert!(verify_citations_format(&citations).is_err());
     }
     #[test]
     fn invalid_citation_extra_comma() {
-        let citations = vec![
-            "Hegel 2021".to_string(),
-            "Kant 2020, 123".to_string(),
-            "Hume, 2020".to_string(),
-        ];
+        let citations = citations_from(&["Hegel 2021", "Kant 2020, 123", "Hume, 2020"]);
         assert!(verify_citations_format(&citations).is_err());
     }
     #[test]
+    fn valid_citation_with_full_date() {
+        let markdown = String::from("As reported (Smith 2020, March 3).");
+        let citations = extract_citations_from_markdown(&markdown);
+        assert_eq!(citations, vec!["Smith 2020, March 3"]);
+        assert!(verify_citations_format(&citations).is_ok());
+        assert_eq!(create_citations_set(citations), vec!["Smith 2020"]);
+    }
+    #[test]
+    fn missing_year_reports_line() {
+        let citations = citations_from(&["Kant 2020, 123", "Hegel"]);
+        let err = verify_citations_format(&citations).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Citation is malformed or is missing year at line 2: (Hegel)"
+        );
+    }
+    #[test]
+    fn unmatched_citations_report_lines() {
+        let entries = BiblatexUtils::retrieve_bibliography_entries("tests/mocks/test.bib")
+            .expect("Failed to retrieve bibliography entries");
+        let citations = citations_from(&["Hegel 2010", "Hegel 1807", "Kant 1781"]);
+        let errors = match_citations_to_bibliography(&citations, &build_citation_index(&entries)).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                (2, CitationError::UnmatchedCitation("Hegel 1807".to_string(), None)),
+                (
+                    3,
+                    CitationError::YearMismatch {
+                        citation: "Kant 1781".to_string(),
+                        year: "1781".to_string(),
+                        key: "kant1998critique".to_string(),
+                        entry_year: "1998".to_string(),
+                    }
+                ),
+            ]
+        );
+    }
+    #[test]
     fn valid_citations_set() {
-        let citations = vec![
-            "Hegel 2021".to_string(),
-            "Kant 2020, 123".to_string(),
-            "Hegel 2021".to_string(),
-            "Hegel 2021, 1234".to_string(),
-            "Hegel 2021, 99".to_string(),
-        ];
+        let citations = citations_from(&[
+            "Hegel 2021",
+            "Kant 2020, 123",
+            "Hegel 2021",
+            "Hegel 2021, 1234",
+            "Hegel 2021, 99",
+        ]);
         let citations_set = create_citations_set(citations);
         assert_eq!(citations_set, vec!["Hegel 2021", "Kant 2020"]);
     }
     #[test]
     fn empty_citations_set() {
-        let citations = Vec::<String>::new();
+        let citations = Vec::<Citation>::new();
         let citations_set = create_citations_set(citations);
         assert!(citations_set.is_empty());
     }
     #[test]
     fn invalid_citations_set() {
-        let citations = vec!["Hegel 2021".to_string(), "Kant, 2020, 123".to_string()];
+        let citations = citations_from(&["Hegel 2021", "Kant, 2020, 123"]);
         let citations_set = create_citations_set(citations);
         assert_eq!(citations_set, vec!["Hegel 2021", "Kant"]);
     }
@@ -1,7 +1,36 @@
 use prepyrus::{
-    utils::{Config, LoadOrCreateSettingsTestMode},
+    errors::{CitationError, CitationErrors},
+    inserters::InserterOutcome,
+    utils::{Config, LoadOrCreateSettingsTestMode, Settings},
     Prepyrus,
 };
+use std::path::{Path, PathBuf};
+
+/// A copy of a mock in a temporary directory of its own, removed when dropped,
+/// so that tests writing to the copy leave the mocks read by other tests pristine.
+struct TempMock {
+    dir: PathBuf,
+    path: String,
+}
+
+impl TempMock {
+    fn new(test_name: &str, mock_path: &str) -> Self {
+        let dir = std::env::temp_dir().join("prepyrus_tests").join(test_name);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(Path::new(mock_path).file_name().unwrap());
+        std::fs::copy(mock_path, &path).unwrap();
+        TempMock {
+            dir,
+            path: path.to_string_lossy().to_string(),
+        }
+    }
+}
+
+impl Drop for TempMock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
 
 #[test]
 fn run_verify_with_directory() {
@@ -24,8 +53,8 @@ fn run_verify_with_directory() {
     );
 
     let all_entries = Prepyrus::get_all_bib_entries(&bib_file).unwrap();
-    let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths)).unwrap();
-    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries).unwrap();
+    let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths.clone()), None, None, None).unwrap();
+    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &settings).unwrap();
 
     println!("{:?}", articles_file_data);
     assert!(mode == "verify");
@@ -54,8 +83,8 @@ fn run_verify_with_directory_with_ignored_paths_from_settings() {
     );
 
     let all_entries = Prepyrus::get_all_bib_entries(&bib_file).unwrap();
-    let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths)).unwrap();
-    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries).unwrap();
+    let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths.clone()), None, None, None).unwrap();
+    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &settings).unwrap();
 
     println!("{:?}", articles_file_data);
     assert!(mode == "verify");
@@ -84,8 +113,8 @@ fn run_verify_with_directory_with_ignored_paths_from_cli_args() {
         });
 
         let all_entries = Prepyrus::get_all_bib_entries(&bib_file).unwrap();
-        let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths)).unwrap();
-        let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries).unwrap();
+        let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths.clone()), None, None, None).unwrap();
+        let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &settings).unwrap();
         let ignored_paths_vec: Vec<String> =
             ignored_paths.split(',').map(|s| s.to_string()).collect();
         assert!(mode == "verify");
@@ -128,8 +157,8 @@ fn run_verify_with_single_file() {
     );
 
     let all_entries = Prepyrus::get_all_bib_entries(&bib_file).unwrap();
-    let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths)).unwrap();
-    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries).unwrap();
+    let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths.clone()), None, None, None).unwrap();
+    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &settings).unwrap();
 
     println!("{:?}", articles_file_data);
     assert!(mode == "verify");
@@ -139,10 +168,14 @@ fn run_verify_with_single_file() {
 
 #[test]
 fn run_process_with_single_file() {
+    let mock = TempMock::new(
+        "run_process_with_single_file",
+        "tests/mocks/data/development_to_process.mdx",
+    );
     let args = vec![
         "program_index".to_string(),
         "tests/mocks/test.bib".to_string(),
-        "tests/mocks/data/development_to_process.mdx".to_string(),
+        mock.path.clone(),
         "process".to_string(),
     ];
     let Config {
@@ -158,14 +191,543 @@ fn run_process_with_single_file() {
     );
 
     let all_entries = Prepyrus::get_all_bib_entries(&bib_file).unwrap();
-    let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths)).unwrap();
-    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries).unwrap();
+    let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths.clone()), None, None, None).unwrap();
+    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &settings).unwrap();
 
     println!("{:?}", articles_file_data);
     assert!(mode == "process");
     assert!(articles_file_data.len() == 1);
     assert!(!articles_file_data.is_empty());
 
-    Prepyrus::process(articles_file_data);
+    Prepyrus::process(articles_file_data, &settings);
+}
+
+#[test]
+fn run_verify_and_generate_master_bibliography() {
+    let args = vec![
+        "program_index".to_string(),
+        "tests/mocks/test.bib".to_string(),
+        "tests/mocks/data".to_string(),
+        "verify".to_string(),
+    ];
+    let Config {
+        bib_file,
+        target_path,
+        settings,
+        ..
+    } = Prepyrus::build_config(&args, Some(LoadOrCreateSettingsTestMode::Test)).unwrap();
+
+    let all_entries = Prepyrus::get_all_bib_entries(&bib_file).unwrap();
+    let mdx_paths = Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths.clone()), None, None, None).unwrap();
+    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &settings).unwrap();
+
+    let out_path = "tests/mocks/master_bibliography_output.mdx";
+    Prepyrus::gen_master_bibliography(&articles_file_data, out_path, &settings).unwrap();
+    let master_bibliography = std::fs::read_to_string(out_path).unwrap();
+    std::fs::remove_file(out_path).unwrap();
+
+    assert!(master_bibliography.starts_with("## Bibliography"));
+    assert_eq!(master_bibliography.matches("## Bibliography").count(), 1);
+    assert_eq!(
+        master_bibliography.matches("_Georg Wilhelm Friedrich Hegel: The Science of Logic_").count(),
+        1
+    );
+}
+
+#[test]
+fn run_verify_and_emit_used_bib() {
+    let settings = Settings::default();
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let mdx_paths = vec!["tests/mocks/data/science-of-logic-introduction.mdx".to_string()];
+    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &settings).unwrap();
+
+    let out_path = "tests/mocks/used_output.bib";
+    Prepyrus::gen_used_bib(&articles_file_data, out_path).unwrap();
+    let used_entries = Prepyrus::get_all_bib_entries(out_path).unwrap();
+    std::fs::remove_file(out_path).unwrap();
+
+    let mut used_keys: Vec<&str> = used_entries.iter().map(|entry| entry.key.as_str()).collect();
+    let mut cited_keys: Vec<&str> = articles_file_data[0]
+        .matched_citations
+        .iter()
+        .map(|entry| entry.key.as_str())
+        .collect();
+    used_keys.sort();
+    cited_keys.sort();
+    assert!(!used_keys.is_empty());
+    assert_eq!(used_keys, cited_keys);
+    assert!(used_entries.len() < all_entries.len());
+}
+
+#[test]
+fn generate_master_bibliography_for_empty_corpus() {
+    let out_path = "tests/mocks/master_bibliography_empty_output.mdx";
+    Prepyrus::gen_master_bibliography(&Vec::new(), out_path, &Settings::default()).unwrap();
+    let master_bibliography = std::fs::read_to_string(out_path).unwrap();
+    std::fs::remove_file(out_path).unwrap();
+
+    assert_eq!(
+        master_bibliography,
+        "## Bibliography\n\nNo works are cited in the processed articles.\n"
+    );
+    assert!(!master_bibliography.contains("<div"));
+}
+
+#[test]
+fn run_verify_skips_drafts_unless_included() {
+    fn verified_paths(include_drafts: bool) -> Vec<String> {
+        let mut args = vec![
+            "program_index".to_string(),
+            "tests/mocks/test.bib".to_string(),
+            "tests/mocks/data".to_string(),
+            "verify".to_string(),
+        ];
+        if include_drafts {
+            args.push("--include-drafts".to_string());
+        }
+        let Config {
+            bib_file,
+            target_path,
+            settings,
+            ..
+        } = Prepyrus::build_config(&args, Some(LoadOrCreateSettingsTestMode::Test)).unwrap();
+
+        let all_entries = Prepyrus::get_all_bib_entries(&bib_file).unwrap();
+        let mdx_paths =
+            Prepyrus::get_mdx_paths(&target_path, Some(settings.ignore_paths.clone()), None, None, None).unwrap();
+        Prepyrus::verify(mdx_paths, &all_entries, &settings)
+            .unwrap()
+            .into_iter()
+            .map(|article| article.path)
+            .collect()
+    }
+
+    let draft_path = "tests/mocks/data/draft.mdx".to_string();
+    assert!(!verified_paths(false).contains(&draft_path));
+    assert!(verified_paths(true).contains(&draft_path));
+}
+
+#[test]
+fn run_process_keeps_crlf_line_endings_and_permissions() {
+    let mock = TempMock::new("run_process_keeps_crlf_line_endings_and_permissions", "tests/mocks/data/science-of-logic-introduction.mdx");
+    let mdx_path = mock.path.as_str();
+    let original_content = std::fs::read_to_string(mdx_path)
+        .unwrap()
+        .replace("\r\n", "\n")
+        .replace('\n', "\r\n");
+    std::fs::write(mdx_path, &original_content).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(mdx_path, std::fs::Permissions::from_mode(0o640)).unwrap();
+    }
+
+    let settings = Settings::default();
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let articles_file_data =
+        Prepyrus::verify(vec![mdx_path.to_string()], &all_entries, &settings).unwrap();
+    let report = Prepyrus::process(articles_file_data, &settings);
+
+    let processed_content = std::fs::read_to_string(mdx_path).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(mdx_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+    // Processing again with linked citations keeps the line endings, and a further run changes nothing
+    let link_settings = Settings {
+        link_citations: true,
+        ..Default::default()
+    };
+    let process_again = || {
+        let articles_file_data =
+            Prepyrus::verify(vec![mdx_path.to_string()], &all_entries, &link_settings).unwrap();
+        Prepyrus::process(articles_file_data, &link_settings);
+        std::fs::read_to_string(mdx_path).unwrap()
+    };
+    let linked_content = process_again();
+    let relinked_content = process_again();
+
+    assert!(processed_content.starts_with(&original_content));
+    assert!(processed_content.contains("**Authors**  \r\nFilip Niklas (2024)\r\n"));
+    assert!(processed_content.ends_with("**Notes**\r\n"));
+    assert!(processed_content.contains("## Bibliography"));
+    assert_eq!(report.files.len(), 1);
+    assert_eq!(report.files[0].lines_removed, 0);
+    assert_eq!(
+        report.total_bytes_added(),
+        processed_content.len() - original_content.len()
+    );
+    assert_eq!(
+        processed_content.matches('\n').count(),
+        processed_content.matches("\r\n").count()
+    );
+    assert!(processed_content.ends_with("\r\n"));
+    assert!(!processed_content.ends_with("\r\n\r\n"));
+    assert!(linked_content.contains("[(Hegel 2010, 23/21.27)](#cite-hegel2010logic)"));
+    assert_eq!(
+        linked_content.matches('\n').count(),
+        linked_content.matches("\r\n").count()
+    );
+    assert_eq!(relinked_content, linked_content);
+}
+
+#[test]
+fn run_process_emits_metadata_json() {
+    let mock = TempMock::new("run_process_emits_metadata_json", "tests/mocks/data/science-of-logic-introduction.mdx");
+    let mdx_path = mock.path.as_str();
+
+    let settings = Settings {
+        emit_metadata_json: true,
+        ..Default::default()
+    };
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let articles_file_data =
+        Prepyrus::verify(vec![mdx_path.to_string()], &all_entries, &settings).unwrap();
+    Prepyrus::process(articles_file_data, &settings);
+
+    let metadata_json_path = format!("{}.meta.json", mdx_path);
+    let metadata_json = std::fs::read_to_string(&metadata_json_path).unwrap();
+
+    assert!(metadata_json.contains(&format!("\"path\": \"{}\"", mdx_path)));
+    assert!(metadata_json.contains("\"isArticle\": true"));
+}
+
+#[test]
+fn run_process_bibliography_only() {
+    let mock = TempMock::new("run_process_bibliography_only", "tests/mocks/data/science-of-logic-introduction.mdx");
+    let mdx_path = mock.path.as_str();
+
+    let settings = Settings {
+        bibliography_only: true,
+        ..Default::default()
+    };
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let articles_file_data =
+        Prepyrus::verify(vec![mdx_path.to_string()], &all_entries, &settings).unwrap();
+    let report = Prepyrus::process(articles_file_data, &settings);
+
+    let processed_content = std::fs::read_to_string(mdx_path).unwrap();
+
+    assert_eq!(
+        report.outcome,
+        InserterOutcome {
+            total_articles: 1,
+            total_articles_processed: 1,
+            total_bibliographies_inserted: 1,
+            ..Default::default()
+        }
+    );
+    assert!(processed_content.contains("## Bibliography"));
+    assert!(!processed_content.contains("**Authors**"));
+    assert!(!processed_content.contains("**Notes**"));
+    assert!(processed_content.ends_with("</div>\n"));
+}
+
+#[test]
+fn run_process_then_bibliography_only_keeps_authors() {
+    let mock = TempMock::new("run_process_then_bibliography_only_keeps_authors", "tests/mocks/data/science-of-logic-introduction.mdx");
+    let mdx_path = mock.path.as_str();
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+
+    let settings = Settings::default();
+    let articles_file_data =
+        Prepyrus::verify(vec![mdx_path.to_string()], &all_entries, &settings).unwrap();
+    Prepyrus::process(articles_file_data, &settings);
+    let processed_content = std::fs::read_to_string(mdx_path).unwrap();
+
+    let settings = Settings {
+        bibliography_only: true,
+        ..Default::default()
+    };
+    let articles_file_data =
+        Prepyrus::verify(vec![mdx_path.to_string()], &all_entries, &settings).unwrap();
+    Prepyrus::process(articles_file_data, &settings);
+    let reprocessed_content = std::fs::read_to_string(mdx_path).unwrap();
+
+    assert!(processed_content.contains("**Authors**"));
+    assert_eq!(reprocessed_content, processed_content);
+}
+
+#[test]
+fn run_verify_leaves_files_untouched() {
+    let settings = Settings {
+        ignore_paths: vec!["tests/mocks/data/development.mdx".to_string()],
+        ..Default::default()
+    };
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let mdx_paths =
+        Prepyrus::get_mdx_paths("tests/mocks/data", Some(settings.ignore_paths.clone()), None, None, None).unwrap();
+    let snapshot = |paths: &Vec<String>| -> Vec<(Vec<u8>, std::time::SystemTime)> {
+        paths
+            .iter()
+            .map(|path| {
+                let modified = std::fs::metadata(path).unwrap().modified().unwrap();
+                (std::fs::read(path).unwrap(), modified)
+            })
+            .collect()
+    };
+    let snapshot_before = snapshot(&mdx_paths);
+
+    Prepyrus::verify(mdx_paths.clone(), &all_entries, &settings).unwrap();
+
+    let snapshot_after = snapshot(&mdx_paths);
+    for ((path, before), after) in mdx_paths.iter().zip(snapshot_before).zip(snapshot_after) {
+        assert_eq!(after.0, before.0, "{} was modified", path);
+        assert_eq!(after.1, before.1, "{} was written to", path);
+    }
+}
+
+#[test]
+fn run_verify_and_generate_tag_index() {
+    let settings = Settings {
+        ignore_paths: vec!["tests/mocks/data/development.mdx".to_string()],
+        ..Default::default()
+    };
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let mdx_paths =
+        Prepyrus::get_mdx_paths("tests/mocks/data", Some(settings.ignore_paths.clone()), None, None, None).unwrap();
+    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &settings).unwrap();
+
+    let out_path = "tests/mocks/tag_index.mdx";
+    Prepyrus::gen_tag_index(&articles_file_data, out_path).unwrap();
+    let tag_index = std::fs::read_to_string(out_path).unwrap();
+    std::fs::remove_file(out_path).unwrap();
+
+    assert!(tag_index.starts_with("## Tags\n"));
+    assert!(tag_index.contains(
+        "### logic\n\n\
+        - [First Paragraph](tests/mocks/data/first-paragraph)\n\
+        - [Introduction](tests/mocks/data/science-of-logic-introduction)\n"
+    ));
+    assert!(tag_index.contains(
+        "### metaphysics\n\n- [Introduction](tests/mocks/data/science-of-logic-introduction)\n"
+    ));
+}
+
+#[test]
+fn run_process_then_clean_restores_original() {
+    let mock = TempMock::new("run_process_then_clean_restores_original", "tests/mocks/data/science-of-logic-introduction.mdx");
+    let mdx_path = mock.path.as_str();
+    let original_content = std::fs::read(mdx_path).unwrap();
+
+    let settings = Settings::default();
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let articles_file_data =
+        Prepyrus::verify(vec![mdx_path.to_string()], &all_entries, &settings).unwrap();
+    Prepyrus::process(articles_file_data, &settings);
+    let processed_content = std::fs::read(mdx_path).unwrap();
+
+    let cleaned_count = Prepyrus::clean(vec![mdx_path.to_string()], &settings).unwrap();
+    let cleaned_content = std::fs::read(mdx_path).unwrap();
+
+    assert_ne!(processed_content, original_content);
+    assert_eq!(cleaned_count, 1);
+    assert_eq!(cleaned_content, original_content);
+}
+
+#[test]
+fn run_process_twice_keeps_a_single_bibliography() {
+    let mock = TempMock::new("run_process_twice_keeps_a_single_bibliography", "tests/mocks/data/science-of-logic-introduction.mdx");
+    let mdx_path = mock.path.as_str();
+
+    let settings = Settings::default();
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let mut processed_contents = Vec::new();
+    for _ in 0..2 {
+        let articles_file_data =
+            Prepyrus::verify(vec![mdx_path.to_string()], &all_entries, &settings).unwrap();
+        Prepyrus::process(articles_file_data, &settings);
+        processed_contents.push(std::fs::read_to_string(mdx_path).unwrap());
+    }
+
+    assert_eq!(processed_contents[1], processed_contents[0]);
+    assert_eq!(processed_contents[1].matches("## Bibliography").count(), 1);
+    assert_eq!(processed_contents[1].matches("**Authors**").count(), 1);
+}
+
+#[test]
+fn run_verify_returns_errors_instead_of_exiting() {
+    let mdx_path = "tests/mocks/unmatched_to_verify.mdx";
+    std::fs::write(
+        mdx_path,
+        "---\ntitle: Unmatched\ndescription: Unmatched citation\nisArticle: true\n---\n\nAs Hegel argues (Hegel 1807, 12).\n",
+    )
+    .unwrap();
+
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let result = Prepyrus::verify(vec![mdx_path.to_string()], &all_entries, &Settings::default());
+    let missing_result = Prepyrus::verify(
+        vec!["tests/mocks/missing_to_verify.mdx".to_string()],
+        &all_entries,
+        &Settings::default(),
+    );
+    std::fs::remove_file(mdx_path).unwrap();
+
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("Hegel 1807"));
+    assert!(err.to_string().contains(mdx_path));
+    assert!(missing_result.is_err());
+}
+
+#[test]
+fn run_verify_collects_citation_errors_across_files() {
+    let first_path = "tests/mocks/first_errors_to_verify.mdx";
+    let second_path = "tests/mocks/second_errors_to_verify.mdx";
+    std::fs::write(
+        first_path,
+        "---\ntitle: First\ndescription: Malformed and unmatched\nisArticle: true\n---\n\nAs argued (Hegel, 12) and (Hegel 1807, 12).\n",
+    )
+    .unwrap();
+    std::fs::write(
+        second_path,
+        "---\ntitle: Second\ndescription: Unmatched\nisArticle: true\n---\n\nAs argued (Kant 1781, 3).\n",
+    )
+    .unwrap();
+    let mdx_paths = vec![first_path.to_string(), second_path.to_string()];
+
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let collected_result = Prepyrus::verify(mdx_paths.clone(), &all_entries, &Settings::default());
+    let fail_fast_settings = Settings {
+        fail_fast: true,
+        ..Default::default()
+    };
+    let fail_fast_result = Prepyrus::verify(mdx_paths, &all_entries, &fail_fast_settings);
+    std::fs::remove_file(first_path).unwrap();
+    std::fs::remove_file(second_path).unwrap();
+
+    let err = collected_result.unwrap_err();
+    let citation_errors = err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<CitationErrors>())
+        .expect("Expected collected citation errors");
+    assert_eq!(
+        citation_errors,
+        &CitationErrors(vec![
+            (
+                first_path.to_string(),
+                7,
+                CitationError::MalformedCitation("Hegel, 12".to_string())
+            ),
+            (
+                first_path.to_string(),
+                7,
+                CitationError::UnmatchedCitation("Hegel 1807".to_string(), None)
+            ),
+            (
+                second_path.to_string(),
+                7,
+                CitationError::YearMismatch {
+                    citation: "Kant 1781".to_string(),
+                    year: "1781".to_string(),
+                    key: "kant1998critique".to_string(),
+                    entry_year: "1998".to_string(),
+                }
+            ),
+        ])
+    );
+    let err = fail_fast_result.unwrap_err();
+    assert!(err.to_string().contains(first_path));
+    assert!(!err.to_string().contains(second_path));
+}
+
+#[test]
+fn run_verify_keeps_the_order_of_the_paths() {
+    let mut mdx_paths = Prepyrus::get_mdx_paths(
+        "tests/mocks/data",
+        Some(vec!["tests/mocks/data/development.mdx".to_string()]),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    mdx_paths.sort();
+    mdx_paths.reverse();
+
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let articles_file_data =
+        Prepyrus::verify(mdx_paths.clone(), &all_entries, &Settings::default()).unwrap();
+
+    let article_paths: Vec<String> = articles_file_data
+        .iter()
+        .map(|article| article.path.clone())
+        .collect();
+    let expected_paths: Vec<String> = mdx_paths
+        .into_iter()
+        .filter(|path| article_paths.contains(path))
+        .collect();
+    assert!(article_paths.len() > 1);
+    assert_eq!(article_paths, expected_paths);
+}
+
+#[test]
+fn run_verify_with_multiple_bib_files() {
+    let args = vec![
+        "program_index".to_string(),
+        "tests/mocks/test.bib,tests/mocks/test-topics.bib".to_string(),
+        "tests/mocks/data/science-of-logic-introduction.mdx".to_string(),
+        "verify".to_string(),
+    ];
+    let Config {
+        bib_file,
+        target_path,
+        settings,
+        ..
+    } = Prepyrus::build_config(&args, Some(LoadOrCreateSettingsTestMode::Test)).unwrap();
+
+    let all_entries = Prepyrus::get_all_bib_entries(&bib_file).unwrap();
+    let mdx_paths = Prepyrus::get_mdx_paths(&target_path, None, None, None, None).unwrap();
+    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &settings).unwrap();
+
+    assert!(all_entries.iter().any(|entry| entry.key == "hegel2010logic"));
+    assert!(all_entries.iter().any(|entry| entry.key == "pinkard1994phenomenology"));
+    assert_eq!(articles_file_data.len(), 1);
+}
+
+#[test]
+fn run_verify_with_md_file() {
+    let args = vec![
+        "program_index".to_string(),
+        "tests/mocks/test.bib".to_string(),
+        "tests/mocks/data/plain-markdown.md".to_string(),
+        "verify".to_string(),
+        "--extensions".to_string(),
+        "md".to_string(),
+    ];
+    let Config {
+        bib_file,
+        target_path,
+        settings,
+        ..
+    } = Prepyrus::build_config(&args, Some(LoadOrCreateSettingsTestMode::Test)).unwrap();
+
+    let all_entries = Prepyrus::get_all_bib_entries(&bib_file).unwrap();
+    let mdx_paths =
+        Prepyrus::get_mdx_paths(&target_path, None, settings.extensions.clone(), None, None).unwrap();
+    let articles_file_data = Prepyrus::verify(mdx_paths, &all_entries, &settings).unwrap();
+
+    assert_eq!(articles_file_data.len(), 1);
+    assert_eq!(articles_file_data[0].path, "tests/mocks/data/plain-markdown.md");
+    assert!(articles_file_data[0]
+        .matched_citations
+        .iter()
+        .any(|entry| entry.key == "hegel2010logic"));
 }
 
+#[test]
+fn run_verify_with_report() {
+    let settings = Settings::default();
+    let all_entries = Prepyrus::get_all_bib_entries("tests/mocks/test.bib").unwrap();
+    let mdx_paths = vec![
+        "tests/mocks/data/plain-markdown.md".to_string(),
+        "tests/mocks/data/draft.mdx".to_string(),
+    ];
+    let verify_report = Prepyrus::verify_with_report(mdx_paths, &all_entries, &settings).unwrap();
+
+    assert_eq!(verify_report.total_files, 2);
+    assert_eq!(verify_report.total_articles, 1);
+    assert_eq!(verify_report.total_skipped, 1);
+    assert_eq!(verify_report.total_citations_matched, 1);
+    assert_eq!(verify_report.articles[0].path, "tests/mocks/data/plain-markdown.md");
+}